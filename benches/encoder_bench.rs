@@ -1,4 +1,5 @@
 #![feature(test)]
+#![allow(clippy::approx_constant)]
 extern crate rosc;
 extern crate test;
 
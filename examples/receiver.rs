@@ -7,7 +7,7 @@ use std::str::FromStr;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let usage = format!("Usage {} IP:PORT", &args[0]);
+    let usage = format!("Usage {} IP:PORT", args[0]);
     if args.len() < 2 {
         println!("{}", usage);
         ::std::process::exit(1)
@@ -13,10 +13,7 @@ fn get_addr_from_arg(arg: &str) -> SocketAddrV4 {
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let usage = format!(
-        "Usage: {} HOST_IP:HOST_PORT CLIENT_IP:CLIENT_PORT",
-        &args[0]
-    );
+    let usage = format!("Usage: {} HOST_IP:HOST_PORT CLIENT_IP:CLIENT_PORT", args[0]);
     if args.len() < 3 {
         panic!("{}", usage);
     }
@@ -1,6 +1,6 @@
 extern crate rosc;
 
-use rosc::encoder::{self, Output};
+use rosc::encoder;
 use rosc::{OscMessage, OscPacket, OscType};
 use std::io::Write;
 use std::net::{SocketAddrV4, TcpStream};
@@ -14,7 +14,7 @@ fn get_addr_from_arg(arg: &str) -> SocketAddrV4 {
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let usage = format!("Usage: {} IP:PORT", &args[0]);
+    let usage = format!("Usage: {} IP:PORT", args[0]);
     if args.len() < 2 {
         panic!("{}", usage);
     }
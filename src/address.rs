@@ -28,6 +28,11 @@ impl OscAddress {
             Err(e) => Err(e),
         }
     }
+
+    /// Returns the address as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
 impl Display for OscAddress {
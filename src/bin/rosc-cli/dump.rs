@@ -0,0 +1,191 @@
+use crate::transport::{self, Endpoint};
+
+use rosc::address::{Matcher, OscAddress};
+use rosc::{decoder, OscBundle, OscMessage, OscPacket, OscType};
+use serde_json::{json, Value};
+
+use std::sync::Arc;
+
+pub const USAGE: &str = "\
+Usage: rosc-cli dump [OPTIONS] ENDPOINT
+
+Listens on ENDPOINT and prints every received OSC packet.
+
+ENDPOINT is one of udp://HOST:PORT, tcp://HOST:PORT (length prefixed),
+slip+tcp://HOST:PORT (SLIP framed) or unix://PATH.
+
+Options:
+    --json              Print one JSON object per packet
+    --filter PATTERN    Only print messages whose address matches the OSC
+                        address pattern, e.g. /mixer/*/gain";
+
+#[derive(Debug)]
+struct Options {
+    json: bool,
+    filter: Option<Matcher>,
+    endpoint: Endpoint,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut json = false;
+    let mut filter = None;
+    let mut endpoint = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--filter" => {
+                let pattern = args.next().ok_or("--filter requires a pattern")?;
+                filter = Some(Matcher::new(pattern).map_err(|err| err.to_string())?);
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+            _ if endpoint.is_none() => endpoint = Some(arg.parse()?),
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+
+    Ok(Options {
+        json,
+        filter,
+        endpoint: endpoint.ok_or("missing endpoint")?,
+    })
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let options = Arc::new(parse_options(args)?);
+
+    let handler = Arc::clone(&options);
+    transport::listen(&options.endpoint, move |source, bytes| {
+        print_packet(&handler, source, bytes)
+    })
+    .map_err(|err| format!("{}: {}", options.endpoint, err))
+}
+
+fn print_packet(options: &Options, source: &str, bytes: &[u8]) {
+    let packet = match decoder::decode_udp(bytes) {
+        Ok((_, packet)) => packet,
+        Err(err) => {
+            eprintln!(
+                "{}: invalid packet of {} bytes: {}",
+                source,
+                bytes.len(),
+                err
+            );
+            return;
+        }
+    };
+    let packet = match &options.filter {
+        Some(matcher) => match filter_packet(packet, matcher) {
+            Some(packet) => packet,
+            None => return,
+        },
+        None => packet,
+    };
+
+    if options.json {
+        println!(
+            "{}",
+            json!({
+                "source": source,
+                "packet": packet_to_json(&packet),
+            })
+        );
+    } else {
+        println!("{} {}", source, packet);
+    }
+}
+
+/// Removes all messages that don't match `matcher`, dropping bundles that
+/// end up empty.
+fn filter_packet(packet: OscPacket, matcher: &Matcher) -> Option<OscPacket> {
+    match packet {
+        OscPacket::Message(msg) => {
+            // Addresses containing pattern characters can't be matched
+            let matches = OscAddress::new(msg.addr.clone())
+                .map(|addr| matcher.match_address(&addr))
+                .unwrap_or(false);
+            if matches {
+                Some(OscPacket::Message(msg))
+            } else {
+                None
+            }
+        }
+        OscPacket::Bundle(bundle) => {
+            let content: Vec<OscPacket> = bundle
+                .content
+                .into_iter()
+                .filter_map(|packet| filter_packet(packet, matcher))
+                .collect();
+            if content.is_empty() {
+                None
+            } else {
+                Some(OscPacket::Bundle(OscBundle {
+                    timetag: bundle.timetag,
+                    content,
+                }))
+            }
+        }
+    }
+}
+
+fn packet_to_json(packet: &OscPacket) -> Value {
+    match packet {
+        OscPacket::Message(msg) => message_to_json(msg),
+        OscPacket::Bundle(bundle) => json!({
+            "timetag": {
+                "seconds": bundle.timetag.seconds,
+                "fractional": bundle.timetag.fractional,
+            },
+            "content": bundle.content.iter().map(packet_to_json).collect::<Vec<_>>(),
+        }),
+    }
+}
+
+fn message_to_json(msg: &OscMessage) -> Value {
+    json!({
+        "address": msg.addr,
+        "args": msg.args.iter().map(arg_to_json).collect::<Vec<_>>(),
+    })
+}
+
+fn arg_to_json(arg: &OscType) -> Value {
+    let (tag, value) = match arg {
+        OscType::Int(v) => ("i", json!(v)),
+        OscType::Float(v) => ("f", json!(v)),
+        OscType::String(v) => ("s", json!(v)),
+        OscType::Blob(v) => (
+            "b",
+            json!(v.iter().map(|b| format!("{:02x}", b)).collect::<String>()),
+        ),
+        OscType::Time(v) => (
+            "t",
+            json!({ "seconds": v.seconds, "fractional": v.fractional }),
+        ),
+        OscType::Long(v) => ("h", json!(v)),
+        OscType::Double(v) => ("d", json!(v)),
+        OscType::Char(v) => ("c", json!(v.to_string())),
+        OscType::Color(v) => (
+            "r",
+            json!({ "red": v.red, "green": v.green, "blue": v.blue, "alpha": v.alpha }),
+        ),
+        OscType::Midi(v) => (
+            "m",
+            json!({ "port": v.port, "status": v.status, "data1": v.data1, "data2": v.data2 }),
+        ),
+        OscType::Bool(v) => (if *v { "T" } else { "F" }, json!(v)),
+        OscType::Array(v) => (
+            "[]",
+            Value::Array(v.content.iter().map(arg_to_json).collect()),
+        ),
+        OscType::Nil => ("N", Value::Null),
+        OscType::Inf => ("I", Value::Null),
+        OscType::Unknown { tag, bytes } => {
+            return json!({
+                "type": tag.to_string(),
+                "value": bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>(),
+            })
+        }
+    };
+    json!({ "type": tag, "value": value })
+}
@@ -0,0 +1,61 @@
+//! Command line tools to inspect, send and relay OSC traffic.
+
+extern crate rosc;
+extern crate serde_json;
+
+mod dump;
+mod relay;
+mod send;
+mod transport;
+
+use std::{env, process};
+
+const USAGE: &str = "\
+Usage: rosc-cli COMMAND [ARGS]
+
+Commands:
+    dump    Print received OSC packets
+    relay   Forward OSC messages based on address patterns
+    send    Send an OSC message
+
+Run `rosc-cli COMMAND --help` for details.";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => exit_with_usage(USAGE),
+    };
+
+    let (usage, result) = match command {
+        "dump" => (dump::USAGE, run(dump::USAGE, args, dump::run)),
+        "relay" => (relay::USAGE, run(relay::USAGE, args, relay::run)),
+        "send" => (send::USAGE, run(send::USAGE, args, send::run)),
+        "-h" | "--help" | "help" => {
+            println!("{}", USAGE);
+            return;
+        }
+        _ => exit_with_usage(USAGE),
+    };
+    if let Err(err) = result {
+        eprintln!("Error: {}\n\n{}", err, usage);
+        process::exit(1);
+    }
+}
+
+fn run(
+    usage: &str,
+    args: &[String],
+    command: fn(&[String]) -> Result<(), String>,
+) -> Result<(), String> {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", usage);
+        return Ok(());
+    }
+    command(args)
+}
+
+fn exit_with_usage(usage: &str) -> ! {
+    eprintln!("{}", usage);
+    process::exit(2)
+}
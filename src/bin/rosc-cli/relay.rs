@@ -0,0 +1,98 @@
+use crate::transport::Endpoint;
+
+use rosc::relay::{ArgType, Relay, Rule};
+
+use std::net::{SocketAddr, UdpSocket};
+
+pub const USAGE: &str = "\
+Usage: rosc-cli relay LISTEN (--route PATTERN [ROUTE OPTIONS])...
+
+Receives OSC packets on the UDP endpoint LISTEN and forwards every message
+matching a route's address pattern to the route's destinations. Messages
+matching no route are dropped, bundles keep their time tags.
+
+Route options apply to the preceding --route:
+    --to udp://HOST:PORT      Forward matching messages to this destination,
+                              can be given multiple times
+    --rewrite FROM TO         Replace the address prefix FROM with TO
+    --coerce [INDEX:]TYPE     Convert all arguments, or only the one at
+                              INDEX, to int, long, float, double, bool or
+                              string
+
+Example:
+    rosc-cli relay udp://0.0.0.0:9000 \\
+        --route '/deck/a/*' --to udp://127.0.0.1:9001 --rewrite /deck/a /mixer/ch/1";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut args = args.iter();
+    let listen = udp_address(args.next().ok_or("missing endpoint to listen on")?)?;
+
+    let mut relay = Relay::new();
+    let mut rule: Option<Rule> = None;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{} requires a value", arg))
+        };
+        if arg == "--route" {
+            if let Some(rule) = rule.take() {
+                relay = relay.rule(rule);
+            }
+            let pattern = value()?;
+            rule = Some(Rule::new(pattern).map_err(|err| err.to_string())?);
+            continue;
+        }
+
+        let current = rule
+            .take()
+            .ok_or_else(|| format!("{} must follow --route", arg))?;
+        rule = Some(match arg.as_str() {
+            "--to" => current.forward_to(udp_address(value()?)?),
+            "--rewrite" => {
+                let from = value()?;
+                current.rewrite_prefix(from, value()?)
+            }
+            "--coerce" => {
+                let coercion = value()?;
+                match coercion.split_once(':') {
+                    Some((index, ty)) => current.coerce_arg(
+                        index
+                            .parse()
+                            .map_err(|_| format!("invalid argument index: {}", index))?,
+                        arg_type(ty)?,
+                    ),
+                    None => current.coerce_args(arg_type(coercion)?),
+                }
+            }
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        });
+    }
+    match rule {
+        Some(rule) => relay = relay.rule(rule),
+        None => return Err("at least one --route is required".to_string()),
+    }
+
+    let socket = UdpSocket::bind(listen).map_err(|err| format!("udp://{}: {}", listen, err))?;
+    let local = socket.local_addr().map_err(|err| err.to_string())?;
+    eprintln!("Listening on udp://{}", local);
+    relay.serve(&socket).map_err(|err| err.to_string())
+}
+
+fn udp_address(endpoint: &str) -> Result<SocketAddr, String> {
+    match endpoint.parse()? {
+        Endpoint::Udp(addr) => Ok(addr),
+        endpoint => Err(format!("only UDP is supported by the relay: {}", endpoint)),
+    }
+}
+
+fn arg_type(name: &str) -> Result<ArgType, String> {
+    match name {
+        "int" => Ok(ArgType::Int),
+        "long" => Ok(ArgType::Long),
+        "float" => Ok(ArgType::Float),
+        "double" => Ok(ArgType::Double),
+        "bool" => Ok(ArgType::Bool),
+        "string" => Ok(ArgType::String),
+        _ => Err(format!("unknown argument type: {}", name)),
+    }
+}
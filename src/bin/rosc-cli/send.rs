@@ -0,0 +1,164 @@
+use crate::transport::{self, Endpoint};
+
+use rosc::address::verify_address_pattern;
+use rosc::{OscArray, OscBundle, OscMessage, OscMidiMessage, OscPacket, OscTime, OscType};
+
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime};
+
+pub const USAGE: &str = "\
+Usage: rosc-cli send [OPTIONS] ENDPOINT ADDRESS [TYPE VALUE]...
+
+Sends a single OSC message to ENDPOINT.
+
+ENDPOINT is one of udp://HOST:PORT, tcp://HOST:PORT (length prefixed),
+slip+tcp://HOST:PORT (SLIP framed) or unix://PATH.
+
+Arguments are given as a type tag followed by its value:
+    i INT32    h INT64    f FLOAT32    d FLOAT64    s STRING    c CHAR
+    b HEX      t SECONDS:FRACTIONAL    m PORT_STATUS_DATA1_DATA2 as HEX
+    r RRGGBBAA
+The tags T, F, N and I take no value, [ and ] enclose an array.
+
+Options:
+    --bundle            Wrap the message in a bundle to be applied immediately
+    --delay SECONDS     Wrap the message in a bundle to be applied SECONDS
+                        from now
+
+Example:
+    rosc-cli send udp://127.0.0.1:9000 /mixer/1/gain f 0.5";
+
+/// Type tags which are followed by a value.
+const VALUE_TAGS: &str = "ihfdscbtmr";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut bundle = false;
+    let mut delay = None;
+
+    let mut args = args.iter();
+    let endpoint: Endpoint = loop {
+        let arg = args.next().ok_or("missing endpoint")?;
+        match arg.as_str() {
+            "--bundle" => bundle = true,
+            "--delay" => {
+                let seconds = args.next().ok_or("--delay requires a duration")?;
+                let seconds = seconds
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(|s| Duration::try_from_secs_f64(s).ok())
+                    .ok_or_else(|| format!("invalid delay: {}", seconds))?;
+                delay = Some(seconds);
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+            _ => break arg.parse()?,
+        }
+    };
+    let addr = args.next().ok_or("missing address")?;
+    verify_address_pattern(addr).map_err(|_| format!("invalid address pattern: {}", addr))?;
+    let msg = OscMessage {
+        addr: addr.to_string(),
+        args: parse_args(args.map(String::as_str))?,
+    };
+
+    let packet = match (bundle, delay) {
+        (false, None) => OscPacket::Message(msg),
+        (_, delay) => {
+            let timetag = match delay {
+                Some(delay) => {
+                    OscTime::try_from(SystemTime::now() + delay).map_err(|err| err.to_string())?
+                }
+                // The special time tag meaning "immediately"
+                None => OscTime::from((0, 1)),
+            };
+            OscPacket::Bundle(OscBundle {
+                timetag,
+                content: vec![OscPacket::Message(msg)],
+            })
+        }
+    };
+
+    transport::send(&endpoint, &packet).map_err(|err| format!("{}: {}", endpoint, err))
+}
+
+/// Parses pairs of type tags and values into message arguments.
+fn parse_args<'a, I: Iterator<Item = &'a str>>(mut args: I) -> Result<Vec<OscType>, String> {
+    let mut parsed = Vec::new();
+    let mut stack: Vec<Vec<OscType>> = Vec::new();
+
+    while let Some(tag) = args.next() {
+        let arg = match tag {
+            "T" => OscType::Bool(true),
+            "F" => OscType::Bool(false),
+            "N" => OscType::Nil,
+            "I" => OscType::Inf,
+            "[" => {
+                stack.push(parsed);
+                parsed = Vec::new();
+                continue;
+            }
+            "]" => {
+                let array = OscType::Array(OscArray { content: parsed });
+                parsed = stack.pop().ok_or("encountered ] outside array")?;
+                array
+            }
+            _ if tag.len() == 1 && VALUE_TAGS.contains(tag) => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for type tag {}", tag))?;
+                parse_value(tag, value)
+                    .ok_or_else(|| format!("invalid value for type tag {}: {}", tag, value))?
+            }
+            _ => return Err(format!("unknown type tag: {}", tag)),
+        };
+        parsed.push(arg);
+    }
+    if !stack.is_empty() {
+        return Err("unclosed [ in arguments".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Parses a single value, returns `None` if it is invalid for `tag`.
+fn parse_value(tag: &str, value: &str) -> Option<OscType> {
+    let arg = match tag {
+        "i" => OscType::Int(value.parse().ok()?),
+        "h" => OscType::Long(value.parse().ok()?),
+        "f" => OscType::Float(value.parse().ok()?),
+        "d" => OscType::Double(value.parse().ok()?),
+        "s" => OscType::String(value.to_string()),
+        "c" => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => OscType::Char(c),
+                _ => return None,
+            }
+        }
+        "b" => OscType::Blob(parse_hex(value)?),
+        "t" => {
+            let (seconds, fractional) = value.split_once(':')?;
+            OscType::Time((seconds.parse().ok()?, fractional.parse().ok()?).into())
+        }
+        "m" => match parse_hex(value)?[..] {
+            [port, status, data1, data2] => OscType::Midi(OscMidiMessage {
+                port,
+                status,
+                data1,
+                data2,
+            }),
+            _ => return None,
+        },
+        "r" => OscType::Color(value.parse().ok()?),
+        _ => return None,
+    };
+    Some(arg)
+}
+
+fn parse_hex(value: &str) -> Option<Vec<u8>> {
+    if value.len() % 2 != 0 || !value.is_ascii() {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok())
+        .collect()
+}
@@ -0,0 +1,257 @@
+use rosc::{encoder, OscPacket};
+
+use std::fmt::{self, Display};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::thread;
+
+/// Largest datagram we are able to receive.
+const MAX_DATAGRAM: usize = 65_536;
+/// Largest packet accepted from a stream, to not allocate arbitrary amounts of memory.
+const MAX_STREAM_PACKET: usize = 16 * 1024 * 1024;
+
+// SLIP special characters, see RFC 1055
+const SLIP_END: u8 = 0xc0;
+const SLIP_ESC: u8 = 0xdb;
+const SLIP_ESC_END: u8 = 0xdc;
+const SLIP_ESC_ESC: u8 = 0xdd;
+
+/// A socket address given on the command line as `scheme://address`.
+#[derive(Clone, Debug)]
+pub enum Endpoint {
+    Udp(SocketAddr),
+    /// TCP with an int32 length prefix per packet, as required by OSC 1.0.
+    Tcp(SocketAddr),
+    /// TCP with SLIP framed packets, as recommended by OSC 1.1.
+    SlipTcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for Endpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, address) = s
+            .split_once("://")
+            .ok_or_else(|| format!("missing scheme in {:?}, e.g. udp://127.0.0.1:9000", s))?;
+        match scheme {
+            "udp" => resolve(address).map(Endpoint::Udp),
+            "tcp" => resolve(address).map(Endpoint::Tcp),
+            "slip+tcp" => resolve(address).map(Endpoint::SlipTcp),
+            #[cfg(unix)]
+            "unix" => Ok(Endpoint::Unix(PathBuf::from(address))),
+            _ => Err(format!("unsupported scheme: {}", scheme)),
+        }
+    }
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Endpoint::Udp(addr) => write!(f, "udp://{}", addr),
+            Endpoint::Tcp(addr) => write!(f, "tcp://{}", addr),
+            Endpoint::SlipTcp(addr) => write!(f, "slip+tcp://{}", addr),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+fn resolve(address: &str) -> Result<SocketAddr, String> {
+    address
+        .to_socket_addrs()
+        .map_err(|err| format!("invalid address {:?}: {}", address, err))?
+        .next()
+        .ok_or_else(|| format!("{:?} did not resolve to an address", address))
+}
+
+/// Receives raw OSC packets on `endpoint` and calls `handle` with the
+/// sender and the packet bytes, until an I/O error occurs.
+///
+/// Stream connections are served on their own thread each.
+pub fn listen<F>(endpoint: &Endpoint, handle: F) -> io::Result<()>
+where
+    F: Fn(&str, &[u8]) + Clone + Send + 'static,
+{
+    match endpoint {
+        Endpoint::Udp(addr) => {
+            let socket = UdpSocket::bind(addr)?;
+            eprintln!("Listening on udp://{}", socket.local_addr()?);
+            let mut buf = vec![0u8; MAX_DATAGRAM];
+            loop {
+                let (size, source) = socket.recv_from(&mut buf)?;
+                handle(&source.to_string(), &buf[..size]);
+            }
+        }
+        Endpoint::Tcp(addr) => serve_tcp("tcp", addr, move |stream, source| {
+            read_length_prefixed(stream, |bytes| handle(source, bytes))
+        }),
+        Endpoint::SlipTcp(addr) => serve_tcp("slip+tcp", addr, move |stream, source| {
+            read_slip(stream, |bytes| handle(source, bytes))
+        }),
+        #[cfg(unix)]
+        Endpoint::Unix(path) => {
+            let socket = UnixDatagram::bind(path)?;
+            eprintln!("Listening on unix://{}", path.display());
+            let mut buf = vec![0u8; MAX_DATAGRAM];
+            loop {
+                let (size, source) = socket.recv_from(&mut buf)?;
+                let source = source
+                    .as_pathname()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_else(|| "unnamed".to_string());
+                handle(&source, &buf[..size]);
+            }
+        }
+    }
+}
+
+/// Sends `packet` to `endpoint`, using the framing of its transport.
+pub fn send(endpoint: &Endpoint, packet: &OscPacket) -> io::Result<()> {
+    let bytes = encoder::encode(packet)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err.to_string()))?;
+    match endpoint {
+        Endpoint::Udp(addr) => {
+            let local: SocketAddr = if addr.is_ipv4() {
+                ([0, 0, 0, 0], 0).into()
+            } else {
+                ([0u16; 8], 0).into()
+            };
+            UdpSocket::bind(local)?.send_to(&bytes, addr)?;
+        }
+        Endpoint::Tcp(addr) => {
+            let mut stream = TcpStream::connect(addr)?;
+            stream.write_all(&(bytes.len() as u32).to_be_bytes())?;
+            stream.write_all(&bytes)?;
+        }
+        Endpoint::SlipTcp(addr) => {
+            TcpStream::connect(addr)?.write_all(&slip_encode(&bytes))?;
+        }
+        #[cfg(unix)]
+        Endpoint::Unix(path) => {
+            UnixDatagram::unbound()?.send_to(&bytes, path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Accepts connections on `addr` and serves each of them on its own thread.
+fn serve_tcp<F>(scheme: &str, addr: &SocketAddr, serve: F) -> io::Result<()>
+where
+    F: Fn(TcpStream, &str) -> io::Result<()> + Clone + Send + 'static,
+{
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("Listening on {}://{}", scheme, listener.local_addr()?);
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let serve = serve.clone();
+        thread::spawn(move || {
+            let source = stream
+                .peer_addr()
+                .map(|addr| addr.to_string())
+                .unwrap_or_default();
+            if let Err(err) = serve(stream, &source) {
+                eprintln!("Connection from {} failed: {}", source, err);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Reads length prefixed packets from `stream` until it is closed.
+fn read_length_prefixed<F: FnMut(&[u8])>(mut stream: TcpStream, mut handle: F) -> io::Result<()> {
+    let mut buf = Vec::new();
+    loop {
+        let mut length = [0u8; 4];
+        match stream.read_exact(&mut length) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err),
+        }
+        let length = u32::from_be_bytes(length) as usize;
+        if length > MAX_STREAM_PACKET {
+            return Err(too_large(length));
+        }
+        buf.resize(length, 0);
+        stream.read_exact(&mut buf)?;
+        handle(&buf);
+    }
+}
+
+/// Reads SLIP framed packets from `stream` until it is closed.
+fn read_slip<F: FnMut(&[u8])>(stream: TcpStream, mut handle: F) -> io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let mut frame = Vec::new();
+    loop {
+        frame.clear();
+        // Stop buffering once a frame can't fit anymore, peers may never send END
+        let read = (&mut stream)
+            .take(MAX_STREAM_PACKET as u64 + 1)
+            .read_until(SLIP_END, &mut frame)?;
+        if read == 0 {
+            return Ok(());
+        }
+        if frame.last() == Some(&SLIP_END) {
+            frame.pop();
+        }
+        if frame.len() > MAX_STREAM_PACKET {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "SLIP frame exceeds the size limit of {} bytes",
+                    MAX_STREAM_PACKET
+                ),
+            ));
+        }
+        // Empty frames come from the leading END byte of double-END framing
+        if !frame.is_empty() {
+            handle(&slip_decode(&frame));
+        }
+    }
+}
+
+fn too_large(length: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("packet of {} bytes exceeds the size limit", length),
+    )
+}
+
+/// Frames `bytes` with an END byte on both sides and escapes the special characters.
+fn slip_encode(bytes: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(bytes.len() + 2);
+    frame.push(SLIP_END);
+    for &b in bytes {
+        match b {
+            SLIP_END => frame.extend_from_slice(&[SLIP_ESC, SLIP_ESC_END]),
+            SLIP_ESC => frame.extend_from_slice(&[SLIP_ESC, SLIP_ESC_ESC]),
+            _ => frame.push(b),
+        }
+    }
+    frame.push(SLIP_END);
+    frame
+}
+
+fn slip_decode(frame: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(frame.len());
+    let mut escaped = false;
+    for &b in frame {
+        match (escaped, b) {
+            (false, SLIP_ESC) => {
+                escaped = true;
+                continue;
+            }
+            (true, SLIP_ESC_END) => bytes.push(SLIP_END),
+            (true, SLIP_ESC_ESC) => bytes.push(SLIP_ESC),
+            // Protocol violation, keep the byte as is
+            (_, b) => bytes.push(b),
+        }
+        escaped = false;
+    }
+    bytes
+}
@@ -0,0 +1,211 @@
+use crate::alloc::string::String;
+use crate::types::OscColor;
+use core::{fmt, str::FromStr};
+
+/// Conversions between [`OscColor`] and other color representations.
+///
+/// Normalized components are `f32`s from `0.0` to `1.0`, out of range values
+/// are clamped. Hues are given in degrees and wrap around at 360.
+///
+/// # Example
+///
+/// ```
+/// use rosc::OscColor;
+///
+/// let orange: OscColor = "#ff8000ff".parse().unwrap();
+/// assert_eq!(orange.to_rgba_u32(), 0xff8000ff);
+/// assert_eq!(orange.to_hex(), "#ff8000ff");
+/// assert_eq!(OscColor::from_hsv(30.0, 1.0, 1.0), orange);
+///
+/// let (red, green, blue, alpha) = orange.into();
+/// assert_eq!((red, green, blue, alpha), (1.0, 128.0 / 255.0, 0.0, 1.0));
+/// ```
+impl OscColor {
+    /// Creates a color from a packed `0xRRGGBBAA` value.
+    pub fn from_rgba_u32(rgba: u32) -> Self {
+        rgba.to_be_bytes().into()
+    }
+
+    /// Returns the color packed as `0xRRGGBBAA`.
+    pub fn to_rgba_u32(&self) -> u32 {
+        u32::from_be_bytes([self.red, self.green, self.blue, self.alpha])
+    }
+
+    /// Returns the color as lowercase `#rrggbbaa` hex string.
+    pub fn to_hex(&self) -> String {
+        format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            self.red, self.green, self.blue, self.alpha
+        )
+    }
+
+    /// Creates an opaque color from hue, saturation and value.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+        let chroma = value * saturation;
+        from_chroma(hue, chroma, value - chroma)
+    }
+
+    /// Returns hue, saturation and value, the alpha channel is ignored.
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (hue, min, max) = self.hue();
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        (hue, saturation, max)
+    }
+
+    /// Creates an opaque color from hue, saturation and lightness.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - abs(2.0 * lightness - 1.0)) * saturation;
+        from_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// Returns hue, saturation and lightness, the alpha channel is ignored.
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (hue, min, max) = self.hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max > min {
+            (max - min) / (1.0 - abs(2.0 * lightness - 1.0))
+        } else {
+            0.0
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// Returns the hue and the smallest and largest normalized component.
+    fn hue(&self) -> (f32, f32, f32) {
+        let (red, green, blue) = (
+            normalize(self.red),
+            normalize(self.green),
+            normalize(self.blue),
+        );
+        let max = red.max(green).max(blue);
+        let min = red.min(green).min(blue);
+        let delta = max - min;
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == red {
+            60.0 * ((green - blue) / delta)
+        } else if max == green {
+            60.0 * ((blue - red) / delta + 2.0)
+        } else {
+            60.0 * ((red - green) / delta + 4.0)
+        };
+        (wrap_hue(hue), min, max)
+    }
+}
+
+/// Creates an opaque color from the hue, chroma and the amount added to
+/// each component.
+fn from_chroma(hue: f32, chroma: f32, offset: f32) -> OscColor {
+    let sector = wrap_hue(hue) / 60.0;
+    let x = chroma * (1.0 - abs(sector % 2.0 - 1.0));
+    let (red, green, blue) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    (red + offset, green + offset, blue + offset, 1.0).into()
+}
+
+fn wrap_hue(hue: f32) -> f32 {
+    let hue = hue % 360.0;
+    if hue < 0.0 {
+        // Tiny negative hues would round up to 360
+        (hue + 360.0) % 360.0
+    } else {
+        hue
+    }
+}
+
+// `f32::abs` is not available without std
+fn abs(v: f32) -> f32 {
+    if v < 0.0 {
+        -v
+    } else {
+        v
+    }
+}
+
+fn normalize(component: u8) -> f32 {
+    f32::from(component) / 255.0
+}
+
+fn denormalize(component: f32) -> u8 {
+    // NaN is converted to 0
+    (component.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+}
+
+/// Converts a packed `0xRRGGBBAA` value.
+impl From<u32> for OscColor {
+    fn from(rgba: u32) -> Self {
+        OscColor::from_rgba_u32(rgba)
+    }
+}
+
+impl From<OscColor> for u32 {
+    fn from(color: OscColor) -> Self {
+        color.to_rgba_u32()
+    }
+}
+
+/// Converts normalized `(red, green, blue, alpha)` components.
+impl From<(f32, f32, f32, f32)> for OscColor {
+    fn from((red, green, blue, alpha): (f32, f32, f32, f32)) -> Self {
+        OscColor {
+            red: denormalize(red),
+            green: denormalize(green),
+            blue: denormalize(blue),
+            alpha: denormalize(alpha),
+        }
+    }
+}
+
+impl From<OscColor> for (f32, f32, f32, f32) {
+    fn from(color: OscColor) -> Self {
+        (
+            normalize(color.red),
+            normalize(color.green),
+            normalize(color.blue),
+            normalize(color.alpha),
+        )
+    }
+}
+
+/// An error returned when parsing an [`OscColor`] from a string fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseColorError;
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid color, expected #RRGGBB or #RRGGBBAA")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseColorError {}
+
+/// Parses `#RRGGBBAA` or `#RRGGBB` hex strings, the `#` is optional and
+/// colors without alpha are opaque.
+impl FromStr for OscColor {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParseColorError);
+        }
+        let rgba = match hex.len() {
+            6 => u32::from_str_radix(hex, 16).map(|rgb| rgb << 8 | 0xff),
+            8 => u32::from_str_radix(hex, 16),
+            _ => return Err(ParseColorError),
+        };
+        rgba.map(OscColor::from_rgba_u32)
+            .map_err(|_| ParseColorError)
+    }
+}
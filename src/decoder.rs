@@ -1,32 +1,544 @@
 use crate::alloc::{
+    collections::BTreeMap,
     string::{String, ToString},
     vec::Vec,
 };
-use crate::errors::OscError;
+use crate::errors::{DecodeError, OscError, PacketElement};
 use crate::types::{
     OscArray, OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscTime, OscType,
 };
+use core::{fmt, mem};
 
-use nom::bytes::complete::{tag, take, take_till};
-use nom::combinator::{map, map_parser};
-use nom::multi::many0;
+use nom::bytes::complete::take;
+use nom::combinator::map;
+use nom::error::ErrorKind;
 use nom::number::complete::{be_f32, be_f64, be_i32, be_i64, be_u32};
-use nom::sequence::terminated;
 use nom::Offset;
 use nom::{combinator::map_res, sequence::tuple, Err, IResult};
 
 /// Common MTU size for ethernet
 pub const MTU: usize = 1536;
 
+/// The type tags of the built-in OSC types.
+pub(crate) const TYPE_TAGS: &str = "fdihstbrTFNIcm";
+
+/// The wire layout of an extension type's argument data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TypeLayout {
+    /// A fixed number of bytes, padded to a multiple of 4. Use `Fixed(0)`
+    /// for types without argument data.
+    Fixed(usize),
+    /// A big-endian int32 size followed by that many bytes, padded like a
+    /// blob.
+    Sized,
+    /// A null terminated string, padded like an OSC-string.
+    String,
+}
+
+impl TypeLayout {
+    /// Returns the encoded argument data for the `payload`, e.g. for
+    /// creating an [`OscType::Unknown`].
+    ///
+    /// An error is returned if the payload doesn't fit into the layout.
+    pub fn encode(&self, payload: &[u8]) -> Result<Vec<u8>, OscError> {
+        let mut bytes = match *self {
+            TypeLayout::Fixed(size) if payload.len() != size => {
+                return Err(OscError::BadArg(format!(
+                    "Expected {} bytes but got {}",
+                    size,
+                    payload.len()
+                )))
+            }
+            TypeLayout::Fixed(_) => payload.to_vec(),
+            TypeLayout::Sized => {
+                let size = u32::try_from(payload.len())
+                    .map_err(|_| OscError::BadArg("Payload is too large".to_string()))?;
+                let mut bytes = size.to_be_bytes().to_vec();
+                bytes.extend_from_slice(payload);
+                bytes
+            }
+            TypeLayout::String if payload.contains(&0) => {
+                return Err(OscError::BadArg(
+                    "String payload contains a null byte".to_string(),
+                ))
+            }
+            TypeLayout::String => {
+                let mut bytes = payload.to_vec();
+                bytes.push(0);
+                bytes
+            }
+        };
+        bytes.resize(crate::encoder::pad(bytes.len() as u64) as usize, 0);
+        Ok(bytes)
+    }
+}
+
+/// Decodes the payload of an extension argument, without size prefix,
+/// terminator and padding.
+pub type DecodeFn = fn(&[u8]) -> Result<OscType, OscError>;
+
+/// How strictly packets are checked against the OSC specification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Profile {
+    #[default]
+    Standard,
+    Lenient,
+    Strict,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Limits {
+    bundle_depth: usize,
+    array_depth: usize,
+    args: usize,
+    data_len: usize,
+    allocation: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            bundle_depth: usize::MAX,
+            array_depth: usize::MAX,
+            args: usize::MAX,
+            data_len: usize::MAX,
+            allocation: usize::MAX,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Extension {
+    layout: TypeLayout,
+    decode: Option<DecodeFn>,
+}
+
+/// Options controlling how packets are decoded.
+///
+/// By default an unknown type tag fails the whole packet since the size of
+/// its argument data is unknown. Extension types can be registered with
+/// their [`TypeLayout`] and are decoded as [`OscType::Unknown`], or by a
+/// custom decode function. Tags of the built-in types can't be overridden.
+///
+/// In passthrough mode the first unregistered type tag captures all of the
+/// message's remaining argument data, the arguments following it are
+/// decoded as `OscType::Unknown` with empty `bytes`. Encoding the message
+/// again reproduces the original bytes, so relays can forward messages they
+/// don't understand.
+///
+/// The [lenient](DecodeOptions::lenient) profile recovers from common
+/// deviations of non-conforming senders, see [`DecodeWarning`] and
+/// [`decode_udp_with_warnings`]. The [strict](DecodeOptions::strict) profile
+/// rejects deviations which are accepted by default.
+///
+/// Limits protect receivers against hostile input, see
+/// [`max_bundle_depth`](DecodeOptions::max_bundle_depth) and the following
+/// methods. Exceeding a limit fails the whole packet, also inside of
+/// bundles.
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, DecodeOptions, TypeLayout};
+/// use rosc::{OscPacket, OscType};
+///
+/// // A message with a symbol argument "S" followed by an int32
+/// let packet = b"/sym\0\0\0\0,Si\0abc\0\0\0\0\x2a";
+/// assert!(decoder::decode_udp(packet).is_err());
+///
+/// let options = DecodeOptions::new().extension('S', TypeLayout::String);
+/// let (_, msg) = decoder::decode_udp_with(packet, &options).unwrap();
+/// let expected = vec![
+///     OscType::Unknown {
+///         tag: 'S',
+///         bytes: b"abc\0".to_vec(),
+///     },
+///     OscType::Int(42),
+/// ];
+/// assert!(matches!(msg, OscPacket::Message(msg) if msg.args == expected));
+///
+/// let options = DecodeOptions::new().extension_with('S', TypeLayout::String, |payload| {
+///     Ok(OscType::String(String::from_utf8_lossy(payload).into_owned()))
+/// });
+/// let (_, msg) = decoder::decode_udp_with(packet, &options).unwrap();
+/// let expected = vec![OscType::String("abc".to_string()), OscType::Int(42)];
+/// assert!(matches!(msg, OscPacket::Message(msg) if msg.args == expected));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DecodeOptions {
+    extensions: BTreeMap<char, Extension>,
+    passthrough_unknown: bool,
+    profile: Profile,
+    limits: Limits,
+}
+
+impl DecodeOptions {
+    /// Returns the default options, which only accept the built-in types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns options which recover from non-conforming packets instead of
+    /// rejecting them:
+    ///
+    /// * Messages without type tag string, a legacy case allowed by OSC 1.0,
+    ///   are decoded with their argument data as a single blob.
+    /// * Addresses which don't start with `/` are accepted.
+    /// * Strings at the end of the packet may lack their null terminator or
+    ///   padding.
+    /// * Strings which aren't valid UTF-8 are decoded lossily.
+    ///
+    /// Each recovery is reported as a [`DecodeWarning`].
+    pub fn lenient() -> Self {
+        DecodeOptions {
+            profile: Profile::Lenient,
+            ..Self::default()
+        }
+    }
+
+    /// Returns options which reject packets the default decoder accepts
+    /// although they don't conform to the OSC specification:
+    ///
+    /// * Padding bytes which aren't zero.
+    /// * Type tag strings which don't start with `,` or contain a `[` without
+    ///   closing `]`.
+    /// * Bundle element sizes which aren't a multiple of 4, and bundle
+    ///   elements with data following the packet.
+    /// * Invalid address patterns, see
+    ///   [`verify_address_pattern`](crate::address::verify_address_pattern).
+    ///   This check requires the `std` feature.
+    /// * Data following the packet in [`decode_udp_with`].
+    ///
+    /// Invalid bundle elements fail the whole packet instead of ending the
+    /// bundle early.
+    pub fn strict() -> Self {
+        DecodeOptions {
+            profile: Profile::Strict,
+            ..Self::default()
+        }
+    }
+
+    /// Registers an extension type which is decoded as
+    /// [`OscType::Unknown`].
+    pub fn extension(mut self, tag: char, layout: TypeLayout) -> Self {
+        self.extensions.insert(
+            tag,
+            Extension {
+                layout,
+                decode: None,
+            },
+        );
+        self
+    }
+
+    /// Registers an extension type which is decoded by `decode`.
+    pub fn extension_with(mut self, tag: char, layout: TypeLayout, decode: DecodeFn) -> Self {
+        self.extensions.insert(
+            tag,
+            Extension {
+                layout,
+                decode: Some(decode),
+            },
+        );
+        self
+    }
+
+    /// Enables or disables passthrough of unregistered type tags.
+    pub fn passthrough_unknown(mut self, passthrough: bool) -> Self {
+        self.passthrough_unknown = passthrough;
+        self
+    }
+
+    /// Limits how deep bundles may be nested, a bundle which isn't part of
+    /// another bundle has depth 1. Unlimited by default.
+    ///
+    /// Bundles are decoded, encoded and dropped recursively, so receivers of
+    /// untrusted packets should set this to avoid stack overflows.
+    pub fn max_bundle_depth(mut self, depth: usize) -> Self {
+        self.limits.bundle_depth = depth;
+        self
+    }
+
+    /// Limits how deep arrays may be nested. Unlimited by default.
+    ///
+    /// Like bundles, arrays are encoded and dropped recursively.
+    pub fn max_array_depth(mut self, depth: usize) -> Self {
+        self.limits.array_depth = depth;
+        self
+    }
+
+    /// Limits the number of arguments of each message, counting the
+    /// arguments inside of arrays but not the arrays themselves. Unlimited by
+    /// default.
+    pub fn max_args(mut self, count: usize) -> Self {
+        self.limits.args = count;
+        self
+    }
+
+    /// Limits the length of strings, including addresses and type tag
+    /// strings, and of blobs in bytes. Unlimited by default.
+    pub fn max_data_len(mut self, len: usize) -> Self {
+        self.limits.data_len = len;
+        self
+    }
+
+    /// Limits the approximate number of bytes allocated for a decoded
+    /// packet, including its strings, blobs, arguments and bundle elements.
+    /// Unlimited by default.
+    pub fn max_allocation(mut self, len: usize) -> Self {
+        self.limits.allocation = len;
+        self
+    }
+
+    fn is_known(&self, tag: char) -> bool {
+        TYPE_TAGS.contains(tag) || self.extensions.contains_key(&tag)
+    }
+
+    fn is_lenient(&self) -> bool {
+        self.profile == Profile::Lenient
+    }
+
+    fn is_strict(&self) -> bool {
+        self.profile == Profile::Strict
+    }
+}
+
+/// A deviation from the OSC specification which a
+/// [lenient](DecodeOptions::lenient) decoder recovered from.
+///
+/// Offsets are relative to the start of the decoded buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeWarning {
+    /// A message without type tag string at `offset`, its argument data was
+    /// decoded as a blob.
+    MissingTypeTags { offset: usize },
+    /// A message address at `offset` which doesn't start with `/`.
+    InvalidAddress { offset: usize, address: String },
+    /// A string at `offset` without null terminator at the end of the packet.
+    UnterminatedString { offset: usize },
+    /// A string at `offset` without padding at the end of the packet.
+    MissingPadding { offset: usize },
+    /// A string at `offset` which isn't valid UTF-8, invalid sequences were
+    /// replaced by U+FFFD.
+    InvalidUtf8 { offset: usize },
+}
+
+impl fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeWarning::MissingTypeTags { offset } => {
+                write!(f, "message without type tags at offset {}", offset)
+            }
+            DecodeWarning::InvalidAddress { offset, address } => {
+                write!(f, "invalid address {:?} at offset {}", address, offset)
+            }
+            DecodeWarning::UnterminatedString { offset } => {
+                write!(f, "unterminated string at offset {}", offset)
+            }
+            DecodeWarning::MissingPadding { offset } => {
+                write!(f, "string without padding at offset {}", offset)
+            }
+            DecodeWarning::InvalidUtf8 { offset } => {
+                write!(f, "invalid UTF-8 in string at offset {}", offset)
+            }
+        }
+    }
+}
+
+/// The state of decoding a single packet.
+struct Context<'o> {
+    options: &'o DecodeOptions,
+    warnings: Vec<DecodeWarning>,
+    // The elements being decoded, outermost first
+    path: Vec<PacketElement>,
+    // Where decoding failed, recorded by the innermost parser
+    failure: Option<Failure>,
+    // The number of enclosing bundles
+    depth: usize,
+    allocated: usize,
+}
+
+struct Failure {
+    offset: usize,
+    path: Vec<PacketElement>,
+    expected: Option<String>,
+    found: Option<String>,
+}
+
+impl<'o> Context<'o> {
+    fn new(options: &'o DecodeOptions) -> Self {
+        Context {
+            options,
+            warnings: Vec::new(),
+            path: Vec::new(),
+            failure: None,
+            depth: 0,
+            allocated: 0,
+        }
+    }
+
+    /// Fails if data of `len` bytes at `offset` exceeds the length limit.
+    fn check_data_len(&mut self, offset: usize, len: usize) -> Result<(), nom::Err<OscError>> {
+        let limit = self.options.limits.data_len;
+        if len > limit {
+            self.fail(offset, Some(format!("at most {} bytes", limit)), bytes(len));
+            return Err(nom::Err::Failure(OscError::DataTooLong(limit)));
+        }
+        Ok(())
+    }
+
+    /// Accounts for `len` bytes allocated for the data at `offset`, failing
+    /// if this exceeds the allocation limit.
+    fn allocate(&mut self, offset: usize, len: usize) -> Result<(), nom::Err<OscError>> {
+        self.allocated = self.allocated.saturating_add(len);
+        let limit = self.options.limits.allocation;
+        if self.allocated > limit {
+            self.fail(
+                offset,
+                Some(format!("at most {} bytes allocated", limit)),
+                bytes(self.allocated),
+            );
+            return Err(nom::Err::Failure(OscError::AllocationLimitExceeded(limit)));
+        }
+        Ok(())
+    }
+
+    /// Records where decoding failed, unless a parser further down already
+    /// did.
+    fn fail(&mut self, offset: usize, expected: Option<String>, found: Option<String>) {
+        if self.failure.is_none() {
+            self.failure = Some(Failure {
+                offset,
+                path: self.path.clone(),
+                expected,
+                found,
+            });
+        }
+    }
+
+    /// Records where decoding `element` failed.
+    fn fail_at(
+        &mut self,
+        element: PacketElement,
+        offset: usize,
+        expected: Option<String>,
+        found: Option<String>,
+    ) {
+        self.path.push(element);
+        self.fail(offset, expected, found);
+        self.path.pop();
+    }
+
+    /// Decodes `element` starting at `offset`, errors which weren't located
+    /// more precisely are located at `offset`.
+    fn within<'a, T>(
+        &mut self,
+        element: PacketElement,
+        offset: usize,
+        parse: impl FnOnce(&mut Self) -> IResult<&'a [u8], T, OscError>,
+    ) -> IResult<&'a [u8], T, OscError> {
+        self.path.push(element);
+        let result = parse(self);
+        if result.is_err() {
+            self.fail(offset, None, None);
+        }
+        self.path.pop();
+        result
+    }
+
+    fn into_error(self, err: nom::Err<OscError>) -> DecodeError {
+        let error = match err {
+            Err::Incomplete(_) => OscError::BadPacket("Incomplete data"),
+            Err::Error(e) | Err::Failure(e) => e,
+        };
+        match self.failure {
+            Some(Failure {
+                offset,
+                path,
+                expected,
+                found,
+            }) => DecodeError {
+                offset,
+                path,
+                expected,
+                found,
+                error,
+            },
+            None => DecodeError::new(0, error),
+        }
+    }
+}
+
+fn bytes(len: usize) -> Option<String> {
+    Some(format!("{} bytes", len))
+}
+
 /// Takes a bytes slice representing a UDP packet and returns the OSC packet as well as a slice of
 /// any bytes remaining after the OSC packet.
 pub fn decode_udp(msg: &[u8]) -> Result<(&[u8], OscPacket), OscError> {
-    match decode_packet(msg, msg) {
-        Ok((remainder, osc_packet)) => Ok((remainder, osc_packet)),
-        Err(e) => match e {
-            Err::Incomplete(_) => Err(OscError::BadPacket("Incomplete data")),
-            Err::Error(e) | Err::Failure(e) => Err(e),
-        },
+    Ok(decode_udp_with(msg, &DecodeOptions::default())?)
+}
+
+/// Like [`decode_udp`], but decodes packets according to `options` and
+/// returns errors which locate the offending bytes.
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, DecodeOptions};
+/// use rosc::PacketElement;
+///
+/// // The second argument is truncated
+/// let packet = b"/pos\0\0\0\0,ff\0\x3f\x80\0\0\x3f\x80";
+/// let err = decoder::decode_udp_with(packet, &DecodeOptions::new()).unwrap_err();
+/// assert_eq!(err.offset, 16);
+/// assert_eq!(err.path, vec![PacketElement::Argument(1)]);
+/// assert_eq!(err.expected.as_deref(), Some("4 bytes"));
+/// assert_eq!(err.found.as_deref(), Some("2 bytes"));
+/// ```
+pub fn decode_udp_with<'a>(
+    msg: &'a [u8],
+    options: &DecodeOptions,
+) -> Result<(&'a [u8], OscPacket), DecodeError> {
+    decode_udp_with_warnings(msg, options).map(|(remainder, packet, _)| (remainder, packet))
+}
+
+/// Like [`decode_udp_with`], but also returns the warnings about the
+/// non-conforming parts of the packet a [lenient](DecodeOptions::lenient)
+/// decoder recovered from.
+///
+/// # Example
+///
+/// ```
+/// use rosc::decoder::{self, DecodeOptions, DecodeWarning};
+/// use rosc::{OscPacket, OscType};
+///
+/// // A message without type tag string
+/// let packet = b"/legacy\0\0\0\0\x2a";
+/// let (_, packet, warnings) =
+///     decoder::decode_udp_with_warnings(packet, &DecodeOptions::lenient()).unwrap();
+/// assert!(matches!(
+///     packet,
+///     OscPacket::Message(msg) if msg.args == vec![OscType::Blob(vec![0, 0, 0, 42])]
+/// ));
+/// assert_eq!(warnings, vec![DecodeWarning::MissingTypeTags { offset: 8 }]);
+/// ```
+pub fn decode_udp_with_warnings<'a>(
+    msg: &'a [u8],
+    options: &DecodeOptions,
+) -> Result<(&'a [u8], OscPacket, Vec<DecodeWarning>), DecodeError> {
+    let mut ctx = Context::new(options);
+    match decode_packet(msg, msg, &mut ctx) {
+        Ok((remainder, _)) if options.is_strict() && !remainder.is_empty() => Err(DecodeError {
+            expected: Some("end of packet".to_string()),
+            found: bytes(remainder.len()),
+            ..DecodeError::new(
+                msg.offset(remainder),
+                OscError::BadPacket("Trailing data after packet"),
+            )
+        }),
+        Ok((remainder, osc_packet)) => Ok((remainder, osc_packet, ctx.warnings)),
+        Err(e) => Err(ctx.into_error(e)),
     }
 }
 
@@ -42,24 +554,34 @@ pub fn decode_udp(msg: &[u8]) -> Result<(&[u8], OscPacket), OscError> {
 ///
 /// [OSC specification]: https://cnmat.org/OpenSoundControl/OSC-spec.html
 pub fn decode_tcp(msg: &[u8]) -> Result<(&[u8], Option<OscPacket>), OscError> {
-    let (input, osc_packet_length) = match be_u32(msg) {
+    Ok(decode_tcp_with(msg, &DecodeOptions::default())?)
+}
+
+/// Like [`decode_tcp`], but decodes packets according to `options` and
+/// returns errors which locate the offending bytes.
+pub fn decode_tcp_with<'a>(
+    msg: &'a [u8],
+    options: &DecodeOptions,
+) -> Result<(&'a [u8], Option<OscPacket>), DecodeError> {
+    let (input, osc_packet_length) = match be_u32::<_, OscError>(msg) {
         Ok((i, o)) => (i, o),
-        Err(e) => match e {
-            Err::Incomplete(_) => return Err(OscError::BadPacket("Incomplete data")),
-            Err::Error(e) | Err::Failure(e) => return Err(e),
-        },
+        Err(e) => {
+            return Err(DecodeError {
+                expected: bytes(4),
+                found: bytes(msg.len()),
+                ..Context::new(options).into_error(e)
+            })
+        }
     };
 
     if osc_packet_length as usize > msg.len() {
         return Ok((msg, None));
     }
 
-    match decode_packet(input, msg).map(|(remainder, osc_packet)| (remainder, Some(osc_packet))) {
-        Ok((remainder, osc_packet)) => Ok((remainder, osc_packet)),
-        Err(e) => match e {
-            Err::Incomplete(_) => Err(OscError::BadPacket("Incomplete data")),
-            Err::Error(e) | Err::Failure(e) => Err(e),
-        },
+    let mut ctx = Context::new(options);
+    match decode_packet(input, msg, &mut ctx) {
+        Ok((remainder, osc_packet)) => Ok((remainder, Some(osc_packet))),
+        Err(e) => Err(ctx.into_error(e)),
     }
 }
 
@@ -84,19 +606,72 @@ pub fn decode_tcp_vec(msg: &[u8]) -> Result<(&[u8], Vec<OscPacket>), OscError> {
 fn decode_packet<'a>(
     input: &'a [u8],
     original_input: &'a [u8],
+    ctx: &mut Context<'_>,
 ) -> IResult<&'a [u8], OscPacket, OscError> {
+    let offset = original_input.offset(input);
     if input.is_empty() {
+        ctx.fail_at(
+            PacketElement::Address,
+            offset,
+            Some("address or #bundle".to_string()),
+            Some("end of data".to_string()),
+        );
         return Err(nom::Err::Error(OscError::BadPacket("Empty packet.")));
     }
 
-    let (input, addr) = read_osc_string(input, original_input)?;
+    let (input, addr) = ctx.within(PacketElement::Address, offset, |ctx| {
+        read_osc_string(input, original_input, ctx)
+    })?;
 
     match addr.chars().next() {
-        Some('/') => decode_message(addr, input, original_input),
-        Some('#') if &addr == "#bundle" => decode_bundle(input, original_input),
-        _ => Err(nom::Err::Error(OscError::BadPacket(
-            "Invalid message address or bundle tag",
-        ))),
+        #[cfg(feature = "std")]
+        Some('/') if ctx.options.is_strict() => {
+            if let Err(e) = crate::address::verify_address_pattern(&addr) {
+                ctx.fail_at(
+                    PacketElement::Address,
+                    offset,
+                    Some("valid address pattern".to_string()),
+                    Some(format!("{:?}", addr)),
+                );
+                return Err(nom::Err::Failure(e));
+            }
+            decode_message(addr, input, original_input, ctx)
+        }
+        Some('/') => decode_message(addr, input, original_input, ctx),
+        Some('#') if &addr == "#bundle" => {
+            let limit = ctx.options.limits.bundle_depth;
+            if ctx.depth >= limit {
+                ctx.fail_at(
+                    PacketElement::Address,
+                    offset,
+                    Some(format!("at most {} nested bundles", limit)),
+                    Some("#bundle".to_string()),
+                );
+                return Err(nom::Err::Failure(OscError::BundleDepthExceeded(limit)));
+            }
+            ctx.depth += 1;
+            let result = decode_bundle(input, original_input, ctx);
+            ctx.depth -= 1;
+            result
+        }
+        _ if ctx.options.is_lenient() => {
+            ctx.warnings.push(DecodeWarning::InvalidAddress {
+                offset,
+                address: addr.clone(),
+            });
+            decode_message(addr, input, original_input, ctx)
+        }
+        _ => {
+            ctx.fail_at(
+                PacketElement::Address,
+                offset,
+                Some("'/' or #bundle".to_string()),
+                Some(format!("{:?}", addr)),
+            );
+            Err(nom::Err::Error(OscError::BadPacket(
+                "Invalid message address or bundle tag",
+            )))
+        }
     }
 }
 
@@ -104,11 +679,47 @@ fn decode_message<'a>(
     addr: String,
     input: &'a [u8],
     original_input: &'a [u8],
+    ctx: &mut Context<'_>,
 ) -> IResult<&'a [u8], OscPacket, OscError> {
-    let (input, type_tags) = read_osc_string(input, original_input)?;
+    let offset = original_input.offset(input);
+    if ctx.options.is_lenient() && input.first() != Some(&b',') {
+        // Messages without type tag string carry opaque argument data
+        ctx.warnings.push(DecodeWarning::MissingTypeTags { offset });
+        let args = if input.is_empty() {
+            vec![]
+        } else {
+            ctx.check_data_len(offset, input.len())?;
+            ctx.allocate(offset, input.len())?;
+            vec![OscType::Blob(input.to_vec())]
+        };
+        return Ok((
+            &input[input.len()..],
+            OscPacket::Message(OscMessage { addr, args }),
+        ));
+    }
+
+    let (input, type_tags) = ctx.within(PacketElement::TypeTags, offset, |ctx| {
+        read_osc_string(input, original_input, ctx)
+    })?;
+    if ctx.options.is_strict() && !type_tags.starts_with(',') {
+        ctx.fail_at(
+            PacketElement::TypeTags,
+            offset,
+            Some("','".to_string()),
+            Some(
+                type_tags
+                    .chars()
+                    .next()
+                    .map_or("empty type tags".to_string(), |c| format!("{:?}", c)),
+            ),
+        );
+        return Err(nom::Err::Failure(OscError::BadMessage(
+            "Type tag string must start with ,",
+        )));
+    }
 
     if type_tags.len() > 1 {
-        let (input, args) = read_osc_args(input, original_input, type_tags)?;
+        let (input, args) = read_osc_args(input, original_input, type_tags, offset, ctx)?;
         Ok((input, OscPacket::Message(OscMessage { addr, args })))
     } else {
         Ok((input, OscPacket::Message(OscMessage { addr, args: vec![] })))
@@ -118,11 +729,34 @@ fn decode_message<'a>(
 fn decode_bundle<'a>(
     input: &'a [u8],
     original_input: &'a [u8],
+    ctx: &mut Context<'_>,
 ) -> IResult<&'a [u8], OscPacket, OscError> {
-    let (input, (timetag, content)) = tuple((
-        read_time_tag,
-        many0(|input| read_bundle_element(input, original_input)),
-    ))(input)?;
+    let offset = original_input.offset(input);
+    let (mut input, timetag) = ctx.within(PacketElement::TimeTag, offset, |ctx| {
+        require(input, 8, original_input, ctx)?;
+        read_time_tag(input)
+    })?;
+
+    let mut content = Vec::new();
+    loop {
+        let offset = original_input.offset(input);
+        let element = PacketElement::BundleElement(content.len());
+        match ctx.within(element, offset, |ctx| {
+            read_bundle_element(input, original_input, ctx)
+        }) {
+            Ok((remainder, packet)) => {
+                ctx.allocate(offset, mem::size_of::<OscPacket>())?;
+                input = remainder;
+                content.push(packet);
+            }
+            // The bundle ends at the first element which can't be decoded
+            Err(nom::Err::Error(_)) => {
+                ctx.failure = None;
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
 
     Ok((input, OscPacket::Bundle(OscBundle { timetag, content })))
 }
@@ -130,103 +764,345 @@ fn decode_bundle<'a>(
 fn read_bundle_element<'a>(
     input: &'a [u8],
     original_input: &'a [u8],
+    ctx: &mut Context<'_>,
 ) -> IResult<&'a [u8], OscPacket, OscError> {
+    let offset = original_input.offset(input);
+    require(input, 4, original_input, ctx)?;
     let (input, elem_size) = be_u32(input)?;
+    let strict = ctx.options.is_strict();
+    if strict && elem_size % 4 != 0 {
+        ctx.fail(
+            offset,
+            Some("size which is a multiple of 4".to_string()),
+            Some(elem_size.to_string()),
+        );
+        return Err(nom::Err::Failure(OscError::BadBundle(format!(
+            "Bundle element size {} is not a multiple of 4",
+            elem_size
+        ))));
+    }
 
-    map_parser(
-        move |input| {
-            take(elem_size)(input).map_err(|_: nom::Err<OscError>| {
-                nom::Err::Error(OscError::BadBundle(
-                    "Bundle shorter than expected!".to_string(),
-                ))
-            })
-        },
-        |input| decode_packet(input, original_input),
-    )(input)
+    let result = require(input, elem_size as usize, original_input, ctx)
+        .map_err(|_| {
+            nom::Err::Error(OscError::BadBundle(
+                "Bundle shorter than expected!".to_string(),
+            ))
+        })
+        .and_then(|()| {
+            let (element, input) = input.split_at(elem_size as usize);
+            let (remainder, packet) = decode_packet(element, original_input, ctx)?;
+            if strict && !remainder.is_empty() {
+                ctx.fail(
+                    original_input.offset(remainder),
+                    Some("end of bundle element".to_string()),
+                    bytes(remainder.len()),
+                );
+                return Err(nom::Err::Error(OscError::BadBundle(
+                    "Bundle element longer than its packet".to_string(),
+                )));
+            }
+            Ok((input, packet))
+        });
+    match result {
+        // Errors would otherwise end the bundle early
+        Err(nom::Err::Error(e)) if strict => Err(nom::Err::Failure(e)),
+        result => result,
+    }
 }
 
 fn read_osc_string<'a>(
     input: &'a [u8],
     original_input: &'a [u8],
+    ctx: &mut Context<'_>,
 ) -> IResult<&'a [u8], String, OscError> {
-    map_res(
-        terminated(
-            tuple((take_till(|c| c == 0u8), tag(b"\0"))),
-            pad_to_32_bit_boundary(original_input),
-        ),
-        |(str_buf, _null_byte)| {
-            String::from_utf8(str_buf.into())
-                .map_err(OscError::StringError)
-                .map(|s| s.trim_matches(0u8 as char).to_string())
-        },
-    )(input)
+    if ctx.options.is_lenient() {
+        return read_lenient_string(input, original_input, ctx);
+    }
+    let offset = original_input.offset(input);
+    let (input, bytes) = read_terminated(input, original_input, ctx)?;
+    ctx.allocate(offset, bytes.len())?;
+    let string = String::from_utf8(bytes.to_vec()).map_err(|e| {
+        let valid = e.utf8_error().valid_up_to();
+        ctx.fail(
+            offset + valid,
+            Some("UTF-8".to_string()),
+            Some(format!("0x{:02x}", bytes[valid])),
+        );
+        nom::Err::Error(OscError::StringError(e))
+    })?;
+    Ok((input, string))
+}
+
+/// Reads null terminated bytes followed by padding, the terminator is not
+/// included.
+fn read_terminated<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    ctx: &mut Context<'_>,
+) -> IResult<&'a [u8], &'a [u8], OscError> {
+    match input.iter().position(|b| *b == 0) {
+        Some(len) => {
+            ctx.check_data_len(original_input.offset(input), len)?;
+            let (remainder, ()) = skip_padding(&input[len + 1..], original_input, ctx)?;
+            Ok((remainder, &input[..len]))
+        }
+        None => {
+            ctx.fail(
+                original_input.offset(input) + input.len(),
+                Some("null terminator".to_string()),
+                Some("end of data".to_string()),
+            );
+            // The error of the former nom parser, kept for compatibility
+            Err(nom::Err::Error(OscError::ReadError(ErrorKind::Tag)))
+        }
+    }
+}
+
+/// Reads a string, recovering from a missing terminator or padding at the end
+/// of the input and from invalid UTF-8.
+fn read_lenient_string<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    ctx: &mut Context<'_>,
+) -> IResult<&'a [u8], String, OscError> {
+    let offset = original_input.offset(input);
+    let end = &input[input.len()..];
+    let (remainder, bytes) = match input.iter().position(|b| *b == 0) {
+        Some(len) => {
+            let padding = padding_len(offset + len + 1);
+            match input.get(len + 1 + padding..) {
+                Some(remainder) => (remainder, &input[..len]),
+                None => {
+                    ctx.warnings.push(DecodeWarning::MissingPadding { offset });
+                    (end, &input[..len])
+                }
+            }
+        }
+        None => {
+            ctx.warnings
+                .push(DecodeWarning::UnterminatedString { offset });
+            (end, input)
+        }
+    };
+    ctx.check_data_len(offset, bytes.len())?;
+    ctx.allocate(offset, bytes.len())?;
+    let string = match core::str::from_utf8(bytes) {
+        Ok(string) => string.to_string(),
+        Err(_) => {
+            ctx.warnings.push(DecodeWarning::InvalidUtf8 { offset });
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+    };
+    Ok((remainder, string))
 }
 
 fn read_osc_args<'a>(
     mut input: &'a [u8],
     original_input: &'a [u8],
     raw_type_tags: String,
+    type_tags_offset: usize,
+    ctx: &mut Context<'_>,
 ) -> IResult<&'a [u8], Vec<OscType>, OscError> {
-    let type_tags: Vec<char> = raw_type_tags.chars().skip(1).collect();
-
-    let mut args: Vec<OscType> = Vec::with_capacity(type_tags.len());
-    let mut stack: Vec<Vec<OscType>> = Vec::new();
-    for tag in type_tags {
+    // Room for the top-level arguments is reserved up front
+    let reserved = (raw_type_tags.len() - 1).min(ctx.options.limits.args);
+    ctx.allocate(
+        type_tags_offset,
+        reserved.saturating_mul(mem::size_of::<OscType>()),
+    )?;
+    let mut args: Vec<OscType> = Vec::with_capacity(reserved);
+    // The enclosing frames together with the index and offset of the `[`
+    let mut stack: Vec<(Vec<OscType>, usize, usize)> = Vec::new();
+    let mut arg_index = 0;
+    // Set once an unknown type tag captured the remaining argument data
+    let mut passthrough = false;
+    for (index, (pos, tag)) in raw_type_tags.char_indices().skip(1).enumerate() {
+        let tag_offset = type_tags_offset + pos;
         if tag == '[' {
+            let limit = ctx.options.limits.array_depth;
+            if stack.len() >= limit {
+                ctx.fail_at(
+                    PacketElement::TypeTag(index),
+                    tag_offset,
+                    Some(format!("at most {} nested arrays", limit)),
+                    Some("'['".to_string()),
+                );
+                return Err(nom::Err::Failure(OscError::ArrayDepthExceeded(limit)));
+            }
             // array start: save current frame and start a new frame
             // for the array's content
-            stack.push(args);
+            stack.push((args, index, tag_offset));
             args = Vec::new();
+            continue;
         } else if tag == ']' {
             // found the end of the current array:
             // create array object from current frame and step one level up
             let array = OscType::Array(OscArray { content: args });
             match stack.pop() {
-                Some(stashed) => args = stashed,
+                Some((stashed, _, _)) => args = stashed,
                 None => {
+                    ctx.fail_at(
+                        PacketElement::TypeTag(index),
+                        tag_offset,
+                        Some("type tag".to_string()),
+                        Some("']' outside array".to_string()),
+                    );
                     return Err(nom::Err::Error(OscError::BadMessage(
                         "Encountered ] outside array",
-                    )))
+                    )));
                 }
             }
+            if !stack.is_empty() {
+                ctx.allocate(tag_offset, mem::size_of::<OscType>())?;
+            }
             args.push(array);
+            continue;
+        }
+
+        let limit = ctx.options.limits.args;
+        if arg_index >= limit {
+            ctx.fail_at(
+                PacketElement::TypeTag(index),
+                tag_offset,
+                Some(format!("at most {} arguments", limit)),
+                Some(format!("{:?}", tag)),
+            );
+            return Err(nom::Err::Failure(OscError::TooManyArgs(limit)));
+        }
+        if !stack.is_empty() {
+            ctx.allocate(tag_offset, mem::size_of::<OscType>())?;
+        }
+        if passthrough {
+            args.push(OscType::Unknown { tag, bytes: vec![] });
+        } else if ctx.options.is_known(tag) {
+            let offset = original_input.offset(input);
+            let (remainder, arg) =
+                ctx.within(PacketElement::Argument(arg_index), offset, |ctx| {
+                    read_osc_arg(input, original_input, tag, ctx)
+                })?;
+            input = remainder;
+            args.push(arg);
+        } else if ctx.options.passthrough_unknown {
+            ctx.allocate(original_input.offset(input), input.len())?;
+            passthrough = true;
+            args.push(OscType::Unknown {
+                tag,
+                bytes: input.to_vec(),
+            });
+            input = &input[input.len()..];
         } else {
-            let input_and_arg = read_osc_arg(input, original_input, tag)?;
-            input = input_and_arg.0;
-            args.push(input_and_arg.1);
+            ctx.fail_at(
+                PacketElement::TypeTag(index),
+                tag_offset,
+                Some("type tag".to_string()),
+                Some(format!("{:?}", tag)),
+            );
+            return Err(nom::Err::Error(OscError::BadArg(format!(
+                "Type tag \"{}\" is not implemented!",
+                tag
+            ))));
+        }
+        arg_index += 1;
+    }
+    if let Some((_, index, tag_offset)) = stack.pop() {
+        if ctx.options.is_strict() {
+            ctx.fail_at(
+                PacketElement::TypeTag(index),
+                tag_offset,
+                Some("closing ']'".to_string()),
+                Some("end of type tags".to_string()),
+            );
+            return Err(nom::Err::Failure(OscError::BadMessage(
+                "Encountered [ without closing ]",
+            )));
         }
     }
     Ok((input, args))
 }
 
+/// Returns the size of the argument data of built-in types with a fixed
+/// size.
+fn fixed_size(tag: char) -> Option<usize> {
+    match tag {
+        'i' | 'f' | 'c' | 'r' | 'm' => Some(4),
+        'h' | 'd' | 't' => Some(8),
+        _ => None,
+    }
+}
+
 fn read_osc_arg<'a>(
     input: &'a [u8],
     original_input: &'a [u8],
     tag: char,
+    ctx: &mut Context<'_>,
 ) -> IResult<&'a [u8], OscType, OscError> {
+    if let Some(size) = fixed_size(tag) {
+        require(input, size, original_input, ctx)?;
+    }
     match tag {
         'f' => map(be_f32, OscType::Float)(input),
         'd' => map(be_f64, OscType::Double)(input),
         'i' => map(be_i32, OscType::Int)(input),
         'h' => map(be_i64, OscType::Long)(input),
-        's' => read_osc_string(input, original_input)
+        's' => read_osc_string(input, original_input, ctx)
             .map(|(remainder, string)| (remainder, OscType::String(string))),
         't' => read_time_tag(input).map(|(remainder, time)| (remainder, OscType::Time(time))),
-        'b' => read_blob(input, original_input),
+        'b' => read_blob(input, original_input, ctx),
         'r' => read_osc_color(input),
         'T' => Ok((input, true.into())),
         'F' => Ok((input, false.into())),
         'N' => Ok((input, OscType::Nil)),
         'I' => Ok((input, OscType::Inf)),
-        'c' => read_char(input),
+        'c' => read_char(input).map_err(|e| {
+            ctx.fail(
+                original_input.offset(input),
+                Some("Unicode scalar value".to_string()),
+                Some(format!(
+                    "0x{:08x}",
+                    u32::from_be_bytes([input[0], input[1], input[2], input[3]])
+                )),
+            );
+            e
+        }),
         'm' => read_midi_message(input),
-        _ => Err(nom::Err::Error(OscError::BadArg(format!(
-            "Type tag \"{}\" is not implemented!",
-            tag
-        )))),
+        _ => match ctx.options.extensions.get(&tag) {
+            Some(&extension) => read_extension(input, original_input, tag, extension, ctx),
+            None => Err(nom::Err::Error(OscError::BadArg(format!(
+                "Type tag \"{}\" is not implemented!",
+                tag
+            )))),
+        },
     }
 }
 
+fn read_extension<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    type_tag: char,
+    extension: Extension,
+    ctx: &mut Context<'_>,
+) -> IResult<&'a [u8], OscType, OscError> {
+    let (remainder, payload) = match extension.layout {
+        TypeLayout::Fixed(size) => read_padded(input, size, original_input, ctx)?,
+        TypeLayout::Sized => {
+            let (input, size) = read_size(input, original_input, ctx)?;
+            read_padded(input, size, original_input, ctx)?
+        }
+        TypeLayout::String => read_terminated(input, original_input, ctx)?,
+    };
+    let arg = match extension.decode {
+        Some(decode) => decode(payload).map_err(nom::Err::Error)?,
+        None => {
+            let len = input.offset(remainder);
+            ctx.allocate(original_input.offset(input), len)?;
+            OscType::Unknown {
+                tag: type_tag,
+                bytes: input[..len].to_vec(),
+            }
+        }
+    };
+    Ok((remainder, arg))
+}
+
 fn read_char(input: &[u8]) -> IResult<&[u8], OscType, OscError> {
     map_res(be_u32, |b| {
         let opt_char = char::from_u32(b);
@@ -240,13 +1116,38 @@ fn read_char(input: &[u8]) -> IResult<&[u8], OscType, OscError> {
 fn read_blob<'a>(
     input: &'a [u8],
     original_input: &'a [u8],
+    ctx: &mut Context<'_>,
 ) -> IResult<&'a [u8], OscType, OscError> {
-    let (input, size) = be_u32(input)?;
+    let offset = original_input.offset(input);
+    let (input, size) = read_size(input, original_input, ctx)?;
+    let (input, blob) = read_padded(input, size, original_input, ctx)?;
+    ctx.allocate(offset, size)?;
+    Ok((input, OscType::Blob(blob.into())))
+}
 
-    map(
-        terminated(take(size), pad_to_32_bit_boundary(original_input)),
-        |blob| OscType::Blob(blob.into()),
-    )(input)
+/// Reads the int32 size of blob-like data.
+fn read_size<'a>(
+    input: &'a [u8],
+    original_input: &'a [u8],
+    ctx: &mut Context<'_>,
+) -> IResult<&'a [u8], usize, OscError> {
+    require(input, 4, original_input, ctx)?;
+    let (remainder, size) = map(be_u32, |size| size as usize)(input)?;
+    ctx.check_data_len(original_input.offset(input), size)?;
+    Ok((remainder, size))
+}
+
+/// Reads `size` bytes followed by padding.
+fn read_padded<'a>(
+    input: &'a [u8],
+    size: usize,
+    original_input: &'a [u8],
+    ctx: &mut Context<'_>,
+) -> IResult<&'a [u8], &'a [u8], OscError> {
+    require(input, size, original_input, ctx)?;
+    let (data, input) = input.split_at(size);
+    let (input, ()) = skip_padding(input, original_input, ctx)?;
+    Ok((input, data))
 }
 
 fn read_time_tag(input: &[u8]) -> IResult<&[u8], OscTime, OscError> {
@@ -278,15 +1179,49 @@ fn read_osc_color(input: &[u8]) -> IResult<&[u8], OscType, OscError> {
     })(input)
 }
 
-fn pad_to_32_bit_boundary<'a>(
+/// Fails if `input` is shorter than `size` bytes.
+fn require(
+    input: &[u8],
+    size: usize,
+    original_input: &[u8],
+    ctx: &mut Context<'_>,
+) -> Result<(), nom::Err<OscError>> {
+    if input.len() < size {
+        ctx.fail(
+            original_input.offset(input),
+            bytes(size),
+            bytes(input.len()),
+        );
+        return Err(nom::Err::Error(OscError::ReadError(ErrorKind::Eof)));
+    }
+    Ok(())
+}
+
+/// Returns the number of padding bytes following data which ends at
+/// `offset`.
+fn padding_len(offset: usize) -> usize {
+    (4 - offset % 4) % 4
+}
+
+/// Skips the padding, which must consist of zero bytes in strict mode.
+fn skip_padding<'a>(
+    input: &'a [u8],
     original_input: &'a [u8],
-) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], (), OscError> {
-    move |input| {
-        let offset = match original_input.offset(input) % 4 {
-            0 => 0,
-            r => 4 - r % 4,
-        };
-        let (input, _) = take(offset)(input)?;
-        Ok((input, ()))
+    ctx: &mut Context<'_>,
+) -> IResult<&'a [u8], (), OscError> {
+    let offset = original_input.offset(input);
+    let len = padding_len(offset);
+    require(input, len, original_input, ctx)?;
+    let (padding, input) = input.split_at(len);
+    if ctx.options.is_strict() {
+        if let Some(pos) = padding.iter().position(|b| *b != 0) {
+            ctx.fail(
+                offset + pos,
+                Some("zero padding".to_string()),
+                Some(format!("0x{:02x}", padding[pos])),
+            );
+            return Err(nom::Err::Failure(OscError::BadPacket("Non-zero padding")));
+        }
     }
+    Ok((input, ()))
 }
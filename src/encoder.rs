@@ -1,4 +1,8 @@
-use crate::alloc::{string::String, vec::Vec};
+use crate::alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use crate::errors::OscError;
 use crate::types::{OscBundle, OscMessage, OscPacket, OscTime, OscType};
 
 /// Takes a reference to an OSC packet and returns
@@ -27,6 +31,27 @@ pub fn encode(packet: &OscPacket) -> crate::types::Result<Vec<u8>> {
     Ok(bytes)
 }
 
+/// Works exactly the same as [encode()], but [validates](Validate) the
+/// packet first instead of encoding packets which can't be decoded as they
+/// were.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{encoder, OscError, OscMessage, OscPacket};
+///
+/// let packet = OscPacket::Message(OscMessage::new("greet/me"));
+/// assert!(encoder::encode(&packet).is_ok());
+/// assert!(matches!(
+///     encoder::encode_checked(&packet),
+///     Err(OscError::AddressWithoutSlash(_))
+/// ));
+/// ```
+pub fn encode_checked(packet: &OscPacket) -> crate::types::Result<Vec<u8>> {
+    packet.validate()?;
+    encode(packet)
+}
+
 /// Works exactly the same as [encode()]. Except that it prepends the length of the message into
 /// the first 4 bytes of the returned Vec, as per the OSC 1.0 specification.
 pub fn encode_tcp(packet: &OscPacket) -> crate::types::Result<Vec<u8>> {
@@ -66,8 +91,8 @@ pub fn encode_tcp(packet: &OscPacket) -> crate::types::Result<Vec<u8>> {
 /// ```
 pub fn encode_into<O: Output>(packet: &OscPacket, out: &mut O) -> Result<usize, O::Err> {
     match *packet {
-        OscPacket::Message(ref msg) => encode_message(msg, out),
-        OscPacket::Bundle(ref bundle) => encode_bundle(bundle, out),
+        OscPacket::Message(ref msg) => encode_message(msg, &mut Copying(out)),
+        OscPacket::Bundle(ref bundle) => encode_bundle(bundle, &mut Copying(out)),
     }
 }
 
@@ -75,12 +100,15 @@ pub fn encode_into<O: Output>(packet: &OscPacket, out: &mut O) -> Result<usize,
 /// the first 4 bytes of the provided [Output], as per the OSC 1.0 specification.
 pub fn encode_into_tcp<O: Output>(packet: &OscPacket, out: &mut O) -> Result<usize, O::Err> {
     match *packet {
-        OscPacket::Message(ref msg) => encode_message_tcp(msg, out),
-        OscPacket::Bundle(ref bundle) => encode_bundle(bundle, out),
+        OscPacket::Message(ref msg) => encode_message_tcp(msg, &mut Copying(out)),
+        OscPacket::Bundle(ref bundle) => encode_bundle(bundle, &mut Copying(out)),
     }
 }
 
-fn encode_message<O: Output>(msg: &OscMessage, out: &mut O) -> Result<usize, O::Err> {
+fn encode_message<'a, O: PayloadOutput<'a>>(
+    msg: &'a OscMessage,
+    out: &mut O,
+) -> Result<usize, O::Err> {
     let mut written = encode_string_into(&msg.addr, out)?;
 
     written += out.write(b",")?;
@@ -98,57 +126,138 @@ fn encode_message<O: Output>(msg: &OscMessage, out: &mut O) -> Result<usize, O::
     Ok(written)
 }
 
-fn encode_message_tcp<O: Output>(msg: &OscMessage, out: &mut O) -> Result<usize, O::Err> {
-    let length_mark = out.mark(4)?;
-
-    let length = encode_message(msg, out)?;
-    out.place(length_mark, &(length as u32).to_be_bytes())?;
-
-    Ok(length + 4)
+fn encode_message_tcp<'a, O: PayloadOutput<'a>>(
+    msg: &'a OscMessage,
+    out: &mut O,
+) -> Result<usize, O::Err> {
+    encode_len_prefixed(out, || message_len(msg), |out| encode_message(msg, out))
 }
 
-fn encode_bundle<O: Output>(bundle: &OscBundle, out: &mut O) -> Result<usize, O::Err> {
+fn encode_bundle<'a, O: PayloadOutput<'a>>(
+    bundle: &'a OscBundle,
+    out: &mut O,
+) -> Result<usize, O::Err> {
     let mut written = encode_string_into("#bundle", out)?;
     written += encode_time_tag_into(&bundle.timetag, out)?;
 
     for packet in &bundle.content {
-        match *packet {
+        written += match *packet {
             OscPacket::Message(ref m) => {
-                let length_mark = out.mark(4)?;
-
-                let length = encode_message(m, out)?;
-                out.place(length_mark, &(length as u32).to_be_bytes())?;
-
-                written += 4 + length;
+                encode_len_prefixed(out, || message_len(m), |out| encode_message(m, out))?
             }
             OscPacket::Bundle(ref b) => {
-                let length_mark = out.mark(4)?;
-
-                let length = encode_bundle(b, out)?;
-                out.place(length_mark, &(length as u32).to_be_bytes())?;
-
-                written += 4 + length;
+                encode_len_prefixed(out, || bundle_len(b), |out| encode_bundle(b, out))?
             }
-        }
+        };
     }
 
     Ok(written)
 }
 
-fn encode_arg_data<O: Output>(arg: &OscType, out: &mut O) -> Result<usize, O::Err> {
+/// Writes the length of an element followed by the element, returns the
+/// number of bytes written including the length.
+///
+/// The length is filled in after encoding the element unless the output
+/// doesn't support [`place`](Output::place), then it's computed up front.
+fn encode_len_prefixed<O: Output>(
+    out: &mut O,
+    len: impl FnOnce() -> usize,
+    encode: impl FnOnce(&mut O) -> Result<usize, O::Err>,
+) -> Result<usize, O::Err> {
+    if O::CAN_PLACE {
+        let length_mark = out.mark(4)?;
+
+        let length = encode(out)?;
+        out.place(length_mark, &(length as u32).to_be_bytes())?;
+
+        Ok(4 + length)
+    } else {
+        out.write(&(len() as u32).to_be_bytes())?;
+        Ok(4 + encode(out)?)
+    }
+}
+
+/// Returns the number of bytes [`encode`] produces for `packet`, without
+/// encoding it.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{encoder, OscBundle, OscMessage, OscPacket};
+///
+/// let packet = OscPacket::Bundle(OscBundle::at((0, 1)).message(OscMessage::new("/a").arg(1)));
+/// assert_eq!(encoder::encoded_len(&packet), encoder::encode(&packet).unwrap().len());
+/// ```
+pub fn encoded_len(packet: &OscPacket) -> usize {
+    match *packet {
+        OscPacket::Message(ref msg) => message_len(msg),
+        OscPacket::Bundle(ref bundle) => bundle_len(bundle),
+    }
+}
+
+fn message_len(msg: &OscMessage) -> usize {
+    // The leading ',' of the type tag string
+    let type_tags_len = 1 + msg.args.iter().map(arg_type_len).sum::<usize>();
+    pad(msg.addr.len() as u64 + 1) as usize
+        + pad(type_tags_len as u64 + 1) as usize
+        + msg.args.iter().map(arg_data_len).sum::<usize>()
+}
+
+fn bundle_len(bundle: &OscBundle) -> usize {
+    // The "#bundle" tag and the time tag
+    16 + bundle
+        .content
+        .iter()
+        .map(|packet| 4 + encoded_len(packet))
+        .sum::<usize>()
+}
+
+fn arg_type_len(arg: &OscType) -> usize {
+    match *arg {
+        OscType::Unknown { tag, .. } => tag.len_utf8(),
+        OscType::Array(ref x) => 2 + x.content.iter().map(arg_type_len).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+fn arg_data_len(arg: &OscType) -> usize {
+    match *arg {
+        OscType::Int(_)
+        | OscType::Float(_)
+        | OscType::Char(_)
+        | OscType::Midi(_)
+        | OscType::Color(_) => 4,
+        OscType::Long(_) | OscType::Double(_) | OscType::Time(_) => 8,
+        OscType::String(ref x) => pad(x.len() as u64 + 1) as usize,
+        OscType::Blob(ref x) => 4 + pad(x.len() as u64) as usize,
+        OscType::Bool(_) | OscType::Nil | OscType::Inf => 0,
+        OscType::Unknown { ref bytes, .. } => pad(bytes.len() as u64) as usize,
+        OscType::Array(ref x) => x.content.iter().map(arg_data_len).sum(),
+    }
+}
+
+fn encode_arg_data<'a, O: PayloadOutput<'a>>(
+    arg: &'a OscType,
+    out: &mut O,
+) -> Result<usize, O::Err> {
     match *arg {
         OscType::Int(x) => out.write(&x.to_be_bytes()),
         OscType::Long(x) => out.write(&x.to_be_bytes()),
         OscType::Float(x) => out.write(&x.to_be_bytes()),
         OscType::Double(x) => out.write(&x.to_be_bytes()),
         OscType::Char(x) => out.write(&(x as u32).to_be_bytes()),
-        OscType::String(ref x) => encode_string_into(x, out),
+        OscType::String(ref x) => {
+            let padding = pad(x.len() as u64 + 1) as usize - x.len();
+            out.write_payload(x.as_bytes())?;
+            out.write(&[0u8; 4][..padding])?;
+            Ok(x.len() + padding)
+        }
         OscType::Blob(ref x) => {
             let padded_blob_length = pad(x.len() as u64) as usize;
             let padding = padded_blob_length - x.len();
 
             out.write(&(x.len() as u32).to_be_bytes())?;
-            out.write(x)?;
+            out.write_payload(x)?;
 
             if padding > 0 {
                 out.write(&[0u8; 3][..padding])?;
@@ -162,6 +271,14 @@ fn encode_arg_data<O: Output>(arg: &OscType, out: &mut O) -> Result<usize, O::Er
         OscType::Bool(_) => Ok(0),
         OscType::Nil => Ok(0),
         OscType::Inf => Ok(0),
+        OscType::Unknown { ref bytes, .. } => {
+            let padding = pad(bytes.len() as u64) as usize - bytes.len();
+            out.write_payload(bytes)?;
+            if padding > 0 {
+                out.write(&[0u8; 3][..padding])?;
+            }
+            Ok(bytes.len() + padding)
+        }
         OscType::Array(ref x) => {
             let mut written = 0;
             for v in &x.content {
@@ -187,6 +304,7 @@ fn encode_arg_type<O: Output>(arg: &OscType, out: &mut O) -> Result<usize, O::Er
         OscType::Bool(x) => out.write(if x { b"T" } else { b"F" }),
         OscType::Nil => out.write(b"N"),
         OscType::Inf => out.write(b"I"),
+        OscType::Unknown { tag, .. } => out.write(tag.encode_utf8(&mut [0u8; 4]).as_bytes()),
         OscType::Array(ref x) => {
             let mut written = out.write(b"[")?;
 
@@ -200,6 +318,210 @@ fn encode_arg_type<O: Output>(arg: &OscType, out: &mut O) -> Result<usize, O::Er
     }
 }
 
+/// Payloads shorter than this are copied into the scratch buffer of
+/// [`EncodedSlices`] instead of being borrowed, to keep the number of slices
+/// low.
+#[cfg(feature = "std")]
+const BORROW_THRESHOLD: usize = 64;
+
+/// Encodes a packet without copying large payloads, for writing it with
+/// `std::io::Write::write_vectored` or similar.
+///
+/// String, blob and unknown argument payloads of at least 64 bytes are
+/// borrowed from the packet, everything else is written to a small scratch
+/// buffer.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{encoder, OscMessage, OscPacket};
+/// use std::io::Write;
+///
+/// let thumbnail = vec![0xffu8; 4096];
+/// let packet = OscPacket::Message(OscMessage::new("/thumbnail").arg(thumbnail));
+///
+/// let slices = encoder::encode_vectored(&packet).unwrap();
+/// let mut out = Vec::new();
+/// assert_eq!(out.write_vectored(&slices.io_slices()).unwrap(), slices.len());
+/// assert_eq!(out, encoder::encode(&packet).unwrap());
+/// ```
+#[cfg(feature = "std")]
+pub fn encode_vectored(packet: &OscPacket) -> crate::types::Result<EncodedSlices<'_>> {
+    let mut out = EncodedSlices::default();
+
+    // NOTE: The Output implementation for EncodedSlices can't actually produce an error!
+    match *packet {
+        OscPacket::Message(ref msg) => encode_message(msg, &mut out),
+        OscPacket::Bundle(ref bundle) => encode_bundle(bundle, &mut out),
+    }
+    .expect("Failed to write encoded packet into EncodedSlices");
+    out.flush();
+
+    Ok(out)
+}
+
+/// An encoded packet made up of slices which are either borrowed from the
+/// packet or point into a scratch buffer, see [`encode_vectored`].
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+pub struct EncodedSlices<'a> {
+    scratch: Vec<u8>,
+    segments: Vec<Segment<'a>>,
+    // The end of the scratch data which is part of `segments`
+    flushed: usize,
+    len: usize,
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+enum Segment<'a> {
+    Scratch(usize, usize),
+    Borrowed(&'a [u8]),
+}
+
+#[cfg(feature = "std")]
+impl<'a> EncodedSlices<'a> {
+    /// Returns the slices to write in order.
+    pub fn io_slices(&self) -> Vec<std::io::IoSlice<'_>> {
+        self.segments
+            .iter()
+            .map(|segment| match *segment {
+                Segment::Scratch(start, end) => std::io::IoSlice::new(&self.scratch[start..end]),
+                Segment::Borrowed(data) => std::io::IoSlice::new(data),
+            })
+            .collect()
+    }
+
+    /// Returns the length of the encoded packet in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the encoded packet is empty, which never happens
+    /// for packets returned by [`encode_vectored`].
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Copies the slices into a single byte vector.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.io_slices()
+            .iter()
+            .flat_map(|s| s.iter().copied())
+            .collect()
+    }
+
+    fn flush(&mut self) {
+        if self.scratch.len() > self.flushed {
+            self.segments
+                .push(Segment::Scratch(self.flushed, self.scratch.len()));
+            self.flushed = self.scratch.len();
+        }
+    }
+}
+
+/// Writes to the scratch buffer, marks must not span borrowed slices.
+#[cfg(feature = "std")]
+impl Output for EncodedSlices<'_> {
+    type Err = core::convert::Infallible;
+    type Mark = (usize, usize);
+
+    fn mark(&mut self, size: usize) -> Result<Self::Mark, Self::Err> {
+        self.len += size;
+        self.scratch.mark(size)
+    }
+
+    fn place(&mut self, mark: Self::Mark, data: &[u8]) -> Result<(), Self::Err> {
+        self.scratch.place(mark, data)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Err> {
+        self.len += data.len();
+        self.scratch.write(data)
+    }
+}
+
+/// Borrows payloads of at least [`BORROW_THRESHOLD`] bytes.
+#[cfg(feature = "std")]
+impl<'a> PayloadOutput<'a> for EncodedSlices<'a> {
+    fn write_payload(&mut self, data: &'a [u8]) -> Result<usize, Self::Err> {
+        if data.len() < BORROW_THRESHOLD {
+            return self.write(data);
+        }
+        self.flush();
+        self.segments.push(Segment::Borrowed(data));
+        self.len += data.len();
+        Ok(data.len())
+    }
+}
+
+/// Checks values for problems the encoder doesn't detect, which would
+/// produce malformed packets or packets which decode differently:
+///
+/// * Message addresses which don't start with `/`.
+/// * Strings, including addresses, which contain a null byte.
+/// * Nested bundles with a time tag preceding the time tag of their parent.
+/// * Char arguments which aren't ASCII.
+pub trait Validate {
+    /// Returns the first problem found.
+    fn validate(&self) -> crate::types::Result<()>;
+}
+
+impl Validate for OscPacket {
+    fn validate(&self) -> crate::types::Result<()> {
+        match self {
+            OscPacket::Message(msg) => msg.validate(),
+            OscPacket::Bundle(bundle) => bundle.validate(),
+        }
+    }
+}
+
+impl Validate for OscMessage {
+    fn validate(&self) -> crate::types::Result<()> {
+        if !self.addr.starts_with('/') {
+            return Err(OscError::AddressWithoutSlash(self.addr.clone()));
+        }
+        validate_string(&self.addr)?;
+        self.args.iter().try_for_each(Validate::validate)
+    }
+}
+
+impl Validate for OscBundle {
+    fn validate(&self) -> crate::types::Result<()> {
+        for packet in &self.content {
+            match packet {
+                OscPacket::Message(msg) => msg.validate()?,
+                OscPacket::Bundle(bundle) if bundle.timetag < self.timetag => {
+                    return Err(OscError::TimeTagBeforeParent {
+                        parent: self.timetag,
+                        nested: bundle.timetag,
+                    })
+                }
+                OscPacket::Bundle(bundle) => bundle.validate()?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for OscType {
+    fn validate(&self) -> crate::types::Result<()> {
+        match self {
+            OscType::String(s) => validate_string(s),
+            OscType::Char(c) if !c.is_ascii() => Err(OscError::NonAsciiChar(*c)),
+            OscType::Array(array) => array.content.iter().try_for_each(Validate::validate),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn validate_string(s: &str) -> crate::types::Result<()> {
+    if s.contains('\0') {
+        return Err(OscError::InteriorNul(s.to_string()));
+    }
+    Ok(())
+}
+
 /// Null terminates the byte representation of string `s` and
 /// adds null bytes until the length of the result is a
 /// multiple of 4.
@@ -259,6 +581,11 @@ fn encode_time_tag_into<O: Output>(time: &OscTime, out: &mut O) -> Result<usize,
 /// - `WriteOutput<W>` (with feature `std`): A wrapper that
 ///   allows data to be written to any type that implements
 ///   `std::io::Seek + std::io::Write`.
+/// - `StreamOutput<W>` (with feature `std`): A wrapper that
+///   allows data to be written to any type that implements
+///   `std::io::Write`.
+/// - `bytes::BytesMut` (with feature `bytes`): Data will be
+///   appended to the end of the buffer.
 pub trait Output {
     /// The error type which is returned from Output functions.
     type Err;
@@ -266,6 +593,11 @@ pub trait Output {
     /// The type which should be used to indicate the location of a mark.
     type Mark;
 
+    /// Whether `mark` and `place` are supported. If not, the encoder computes
+    /// the lengths of length-prefixed elements before writing them, which
+    /// costs an extra pass over nested bundles.
+    const CAN_PLACE: bool = true;
+
     /// Writes a block of data to the output.
     ///
     /// Note that, unlike `std::io::Write::write`, this
@@ -283,6 +615,44 @@ pub trait Output {
     fn place(&mut self, mark: Self::Mark, data: &[u8]) -> Result<(), Self::Err>;
 }
 
+/// An [`Output`] which may keep string, blob and unknown argument payloads
+/// instead of copying them, this lets [`encode_vectored`] share the encoder.
+trait PayloadOutput<'a>: Output {
+    fn write_payload(&mut self, data: &'a [u8]) -> Result<usize, Self::Err>;
+}
+
+/// Copies payloads into the wrapped [`Output`].
+struct Copying<'o, O>(&'o mut O);
+
+impl<O: Output> Output for Copying<'_, O> {
+    type Err = O::Err;
+    type Mark = O::Mark;
+
+    const CAN_PLACE: bool = O::CAN_PLACE;
+
+    #[inline]
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Err> {
+        self.0.write(data)
+    }
+
+    #[inline]
+    fn mark(&mut self, size: usize) -> Result<Self::Mark, Self::Err> {
+        self.0.mark(size)
+    }
+
+    #[inline]
+    fn place(&mut self, mark: Self::Mark, data: &[u8]) -> Result<(), Self::Err> {
+        self.0.place(mark, data)
+    }
+}
+
+impl<'a, O: Output> PayloadOutput<'a> for Copying<'_, O> {
+    #[inline]
+    fn write_payload(&mut self, data: &'a [u8]) -> Result<usize, Self::Err> {
+        self.0.write(data)
+    }
+}
+
 impl Output for Vec<u8> {
     type Err = core::convert::Infallible;
     type Mark = (usize, usize);
@@ -349,3 +719,80 @@ impl<W: std::io::Seek + std::io::Write> Output for WriteOutput<W> {
         std::io::Write::write_all(&mut self.0, data).map(|_| data.len())
     }
 }
+
+/// A new type which can be used to wrap any type which
+/// implements `std::io::Write` to allow it to be used as an
+/// `Output`, e.g. a `TcpStream` or `Stdout`.
+///
+/// Unlike [`WriteOutput`], it doesn't need to seek back to
+/// fill in lengths since they are computed before writing
+/// the elements. `mark` and `place` always fail.
+///
+/// # Example
+///
+/// ```
+/// use rosc::encoder::{self, StreamOutput};
+/// use rosc::{OscBundle, OscPacket};
+///
+/// let packet = OscPacket::Bundle(OscBundle::at((0, 1)).message("/a"));
+/// let mut out = StreamOutput(Vec::new());
+/// encoder::encode_into(&packet, &mut out).unwrap();
+/// assert_eq!(out.0, encoder::encode(&packet).unwrap());
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct StreamOutput<W>(pub W);
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> Output for StreamOutput<W> {
+    type Err = std::io::Error;
+    type Mark = ();
+
+    const CAN_PLACE: bool = false;
+
+    fn mark(&mut self, _size: usize) -> Result<Self::Mark, Self::Err> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "StreamOutput can't place marks",
+        ))
+    }
+
+    fn place(&mut self, _mark: Self::Mark, _data: &[u8]) -> Result<(), Self::Err> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "StreamOutput can't place marks",
+        ))
+    }
+
+    #[inline]
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Err> {
+        std::io::Write::write_all(&mut self.0, data).map(|_| data.len())
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Output for bytes::BytesMut {
+    type Err = core::convert::Infallible;
+    type Mark = (usize, usize);
+
+    #[inline]
+    fn mark(&mut self, size: usize) -> Result<Self::Mark, Self::Err> {
+        let start = self.len();
+        let end = start + size;
+
+        self.resize(end, 0);
+        Ok((start, end))
+    }
+
+    #[inline]
+    fn place(&mut self, (start, end): Self::Mark, data: &[u8]) -> Result<(), Self::Err> {
+        self[start..end].copy_from_slice(data);
+        Ok(())
+    }
+
+    #[inline]
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Err> {
+        self.extend_from_slice(data);
+        Ok(data.len())
+    }
+}
@@ -1,6 +1,8 @@
+use crate::types::OscTime;
 use alloc::{
     fmt,
     string::{self, String},
+    vec::Vec,
 };
 use nom::error::{ErrorKind, FromExternalError, ParseError};
 #[cfg(feature = "std")]
@@ -21,6 +23,27 @@ pub enum OscError {
     BadAddress(String),
     RegexError(String),
     Unimplemented,
+    /// Bundles are nested deeper than the given limit.
+    BundleDepthExceeded(usize),
+    /// Arrays are nested deeper than the given limit.
+    ArrayDepthExceeded(usize),
+    /// A message has more arguments than the given limit.
+    TooManyArgs(usize),
+    /// A string or blob is longer than the given number of bytes.
+    DataTooLong(usize),
+    /// Decoding would allocate more than the given number of bytes.
+    AllocationLimitExceeded(usize),
+    /// A message address doesn't start with `/`.
+    AddressWithoutSlash(String),
+    /// A string contains a null byte, which would end it when decoded.
+    InteriorNul(String),
+    /// The time tag of a nested bundle precedes the time tag of its parent.
+    TimeTagBeforeParent {
+        parent: OscTime,
+        nested: OscTime,
+    },
+    /// A char argument is not ASCII.
+    NonAsciiChar(char),
 }
 
 impl fmt::Display for OscError {
@@ -38,6 +61,27 @@ impl fmt::Display for OscError {
             OscError::BadAddress(msg) => write!(f, "bad OSC address: {}", msg),
             OscError::RegexError(msg) => write!(f, "OSC address pattern regex error: {}", msg),
             OscError::Unimplemented => write!(f, "unimplemented"),
+            OscError::BundleDepthExceeded(limit) => {
+                write!(f, "bundles nested deeper than {} levels", limit)
+            }
+            OscError::ArrayDepthExceeded(limit) => {
+                write!(f, "arrays nested deeper than {} levels", limit)
+            }
+            OscError::TooManyArgs(limit) => write!(f, "more than {} arguments", limit),
+            OscError::DataTooLong(limit) => write!(f, "data longer than {} bytes", limit),
+            OscError::AllocationLimitExceeded(limit) => {
+                write!(f, "allocating more than {} bytes", limit)
+            }
+            OscError::AddressWithoutSlash(addr) => {
+                write!(f, "address {:?} doesn't start with /", addr)
+            }
+            OscError::InteriorNul(s) => write!(f, "string {:?} contains a null byte", s),
+            OscError::TimeTagBeforeParent { parent, nested } => write!(
+                f,
+                "nested bundle time tag ({}, {}) precedes its parent's time tag ({}, {})",
+                nested.seconds, nested.fractional, parent.seconds, parent.fractional
+            ),
+            OscError::NonAsciiChar(c) => write!(f, "char {:?} is not ASCII", c),
         }
     }
 }
@@ -74,3 +118,98 @@ impl error::Error for OscError {
         }
     }
 }
+
+/// An element of an OSC packet, used to locate [`DecodeError`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PacketElement {
+    /// The address of a message or the `#bundle` tag of a bundle.
+    Address,
+    /// The type tag string of a message.
+    TypeTags,
+    /// The type tag at the given index, not counting the leading `,`.
+    TypeTag(usize),
+    /// The argument at the given index, counting the arguments inside of
+    /// arrays but not the arrays themselves.
+    Argument(usize),
+    /// The time tag of a bundle.
+    TimeTag,
+    /// The element of a bundle at the given index.
+    BundleElement(usize),
+}
+
+impl fmt::Display for PacketElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PacketElement::Address => write!(f, "address"),
+            PacketElement::TypeTags => write!(f, "type tags"),
+            PacketElement::TypeTag(index) => write!(f, "type tag {}", index),
+            PacketElement::Argument(index) => write!(f, "argument {}", index),
+            PacketElement::TimeTag => write!(f, "time tag"),
+            PacketElement::BundleElement(index) => write!(f, "bundle element {}", index),
+        }
+    }
+}
+
+/// An error returned by the `decode_*_with` functions, which locates where
+/// decoding failed.
+#[derive(Debug)]
+pub struct DecodeError {
+    /// The offset of the offending bytes from the start of the decoded data.
+    pub offset: usize,
+    /// The elements being decoded, outermost first, e.g. argument 1 of
+    /// bundle element 0.
+    pub path: Vec<PacketElement>,
+    /// What was expected at `offset`, if known.
+    pub expected: Option<String>,
+    /// What was found at `offset` instead, if known.
+    pub found: Option<String>,
+    /// The underlying error.
+    pub error: OscError,
+}
+
+impl DecodeError {
+    /// Creates an error at `offset` outside of any packet element.
+    pub fn new(offset: usize, error: OscError) -> Self {
+        DecodeError {
+            offset,
+            path: Vec::new(),
+            expected: None,
+            found: None,
+            error,
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.error, self.offset)?;
+        for (i, element) in self.path.iter().enumerate() {
+            f.write_str(if i == 0 { " (" } else { ", " })?;
+            write!(f, "{}", element)?;
+        }
+        if !self.path.is_empty() {
+            f.write_str(")")?;
+        }
+        match (&self.expected, &self.found) {
+            (Some(expected), Some(found)) => {
+                write!(f, ": expected {}, found {}", expected, found)
+            }
+            (Some(expected), None) => write!(f, ": expected {}", expected),
+            (None, Some(found)) => write!(f, ": found {}", found),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl From<DecodeError> for OscError {
+    fn from(err: DecodeError) -> Self {
+        err.error
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
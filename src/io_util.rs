@@ -0,0 +1,18 @@
+use std::io::{self, Read};
+
+/// Fills `buf` or returns `false` if the reader is at its end, retrying
+/// interrupted reads. A reader which ends after filling part of `buf` yields
+/// an `UnexpectedEof` error.
+pub(crate) fn read_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) if read == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => read += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(true)
+}
@@ -21,16 +21,42 @@ extern crate time;
 
 /// Crate specific error types.
 mod errors;
+/// Reading helpers shared by the recording and capture readers.
+#[cfg(feature = "std")]
+mod io_util;
+/// Macros for message and bundle literals.
+mod macros;
+/// Type tag signatures of OSC methods.
+mod signature;
 /// OSC data types, see [OSC 1.0 specification](https://opensoundcontrol.stanford.edu/spec-1_0.html) for details.
 mod types;
 
 pub use crate::errors::*;
+pub use crate::signature::{SignatureMismatch, TypeSignature};
 pub use crate::types::*;
 
 /// Address checking and matching methods
 #[cfg(feature = "std")]
 pub mod address;
+/// Conversions between `OscColor` and other color representations.
+pub mod color;
 /// Provides a decoding method for OSC packets.
 pub mod decoder;
 /// Encodes an `OscPacket` to a byte vector.
 pub mod encoder;
+/// Typed MIDI messages carried by `OscMidiMessage`.
+pub mod midi;
+/// OSCQuery discovery of OSC address spaces over HTTP.
+#[cfg(feature = "oscquery")]
+pub mod oscquery;
+/// Reading OSC-over-UDP traffic from pcap and pcapng capture files.
+#[cfg(feature = "std")]
+pub mod pcap;
+/// Recording OSC traffic to disk and replaying it.
+#[cfg(feature = "std")]
+pub mod recording;
+/// Forwarding OSC packets to other hosts based on address patterns.
+#[cfg(feature = "std")]
+pub mod relay;
+/// Traversing and transforming packets with visitors.
+pub mod visitor;
@@ -0,0 +1,53 @@
+/// Creates an [`OscMessage`](crate::OscMessage) from an address and a list
+/// of arguments, each argument is converted with [`Into<OscType>`](crate::OscType).
+///
+/// # Example
+///
+/// ```
+/// use rosc::{osc, OscMessage, OscType};
+///
+/// let msg = osc!("/synth/1/freq", 440.0f32, "sine", true);
+/// assert_eq!(
+///     msg,
+///     OscMessage {
+///         addr: "/synth/1/freq".to_string(),
+///         args: vec![
+///             OscType::Float(440.0),
+///             OscType::String("sine".to_string()),
+///             OscType::Bool(true),
+///         ],
+///     }
+/// );
+/// assert!(osc!("/synth/1/gate").args.is_empty());
+/// ```
+#[macro_export]
+macro_rules! osc {
+    ($addr:expr $(, $arg:expr)* $(,)?) => {
+        $crate::OscMessage::new($addr)$(.arg($arg))*
+    };
+}
+
+/// Creates an [`OscBundle`](crate::OscBundle) from a time tag and a list of
+/// messages and bundles.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{osc, osc_bundle, OscPacket};
+///
+/// let bundle = osc_bundle!((0, 1);
+///     osc!("/synth/1/freq", 440.0f32),
+///     osc_bundle!((10, 0); osc!("/synth/1/gate", 0)),
+/// );
+/// assert_eq!(bundle.timetag, (0, 1).into());
+/// assert!(matches!(bundle.content[1], OscPacket::Bundle(_)));
+/// ```
+#[macro_export]
+macro_rules! osc_bundle {
+    ($time:expr $(; $($packet:expr),* $(,)?)?) => {{
+        #[allow(unused_mut)]
+        let mut bundle = $crate::OscBundle::at($time);
+        $($(bundle.content.push($crate::OscPacket::from($packet));)*)?
+        bundle
+    }};
+}
@@ -0,0 +1,305 @@
+use crate::types::OscMidiMessage;
+use core::{convert::TryFrom, fmt};
+
+/// A MIDI channel or system message which fits into the three bytes of an
+/// [`OscMidiMessage`].
+///
+/// Channels are zero based, so MIDI channel 1 is `channel: 0`. System
+/// exclusive messages don't fit and are not supported, neither is running
+/// status: every message carries its status byte.
+///
+/// # Example
+///
+/// ```
+/// use rosc::midi::MidiMessage;
+/// use rosc::OscMidiMessage;
+///
+/// let note = MidiMessage::NoteOn {
+///     channel: 0,
+///     key: 60,
+///     velocity: 100,
+/// };
+/// let osc = note.to_osc(1).unwrap();
+/// assert_eq!(osc, OscMidiMessage::from([1, 0x90, 60, 100]));
+/// assert_eq!(osc.message(), Ok(note));
+///
+/// assert!(OscMidiMessage::from([0, 0x90, 200, 0]).message().is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MidiMessage {
+    NoteOff {
+        channel: u8,
+        key: u8,
+        velocity: u8,
+    },
+    NoteOn {
+        channel: u8,
+        key: u8,
+        velocity: u8,
+    },
+    PolyPressure {
+        channel: u8,
+        key: u8,
+        pressure: u8,
+    },
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    ProgramChange {
+        channel: u8,
+        program: u8,
+    },
+    ChannelPressure {
+        channel: u8,
+        pressure: u8,
+    },
+    /// A 14-bit pitch bend, [`PITCH_BEND_CENTER`](MidiMessage::PITCH_BEND_CENTER)
+    /// means no bend.
+    PitchBend {
+        channel: u8,
+        value: u16,
+    },
+    TimeCodeQuarterFrame(u8),
+    /// The 14-bit song position in MIDI beats.
+    SongPosition(u16),
+    SongSelect(u8),
+    TuneRequest,
+    TimingClock,
+    Start,
+    Continue,
+    Stop,
+    ActiveSensing,
+    SystemReset,
+}
+
+/// Errors returned when converting between [`MidiMessage`] and raw bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MidiError {
+    /// The first byte is a data byte, running status is not supported.
+    MissingStatus(u8),
+    /// System exclusive or undefined status bytes.
+    UnsupportedStatus(u8),
+    /// A data byte with the high bit set.
+    InvalidData(u8),
+    /// A channel beyond 15.
+    InvalidChannel(u8),
+    /// A 14-bit value beyond 16383.
+    ValueOutOfRange(u16),
+}
+
+impl fmt::Display for MidiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MidiError::MissingStatus(byte) => {
+                write!(f, "expected a MIDI status byte, found 0x{:02X}", byte)
+            }
+            MidiError::UnsupportedStatus(byte) => {
+                write!(f, "unsupported MIDI status byte 0x{:02X}", byte)
+            }
+            MidiError::InvalidData(byte) => write!(f, "invalid MIDI data byte 0x{:02X}", byte),
+            MidiError::InvalidChannel(channel) => write!(f, "invalid MIDI channel {}", channel),
+            MidiError::ValueOutOfRange(value) => {
+                write!(f, "MIDI value {} exceeds 14 bits", value)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MidiError {}
+
+fn data(byte: u8) -> Result<u8, MidiError> {
+    if byte < 0x80 {
+        Ok(byte)
+    } else {
+        Err(MidiError::InvalidData(byte))
+    }
+}
+
+fn channel(channel: u8) -> Result<u8, MidiError> {
+    if channel < 16 {
+        Ok(channel)
+    } else {
+        Err(MidiError::InvalidChannel(channel))
+    }
+}
+
+/// Splits a 14-bit value into its least and most significant data bytes.
+fn split_14bit(value: u16) -> Result<(u8, u8), MidiError> {
+    if value < 0x4000 {
+        Ok(((value & 0x7f) as u8, (value >> 7) as u8))
+    } else {
+        Err(MidiError::ValueOutOfRange(value))
+    }
+}
+
+fn join_14bit(lsb: u8, msb: u8) -> Result<u16, MidiError> {
+    Ok(u16::from(data(lsb)?) | u16::from(data(msb)?) << 7)
+}
+
+impl MidiMessage {
+    /// The pitch bend value meaning no bend.
+    pub const PITCH_BEND_CENTER: u16 = 0x2000;
+
+    /// Parses a status byte followed by two data bytes. Data bytes which are
+    /// not used by the message are ignored.
+    pub fn from_bytes(bytes: [u8; 3]) -> Result<Self, MidiError> {
+        let [status, data1, data2] = bytes;
+        if status < 0x80 {
+            return Err(MidiError::MissingStatus(status));
+        }
+        let channel = status & 0x0f;
+        let message = match status & 0xf0 {
+            0x80 => MidiMessage::NoteOff {
+                channel,
+                key: data(data1)?,
+                velocity: data(data2)?,
+            },
+            0x90 => MidiMessage::NoteOn {
+                channel,
+                key: data(data1)?,
+                velocity: data(data2)?,
+            },
+            0xa0 => MidiMessage::PolyPressure {
+                channel,
+                key: data(data1)?,
+                pressure: data(data2)?,
+            },
+            0xb0 => MidiMessage::ControlChange {
+                channel,
+                controller: data(data1)?,
+                value: data(data2)?,
+            },
+            0xc0 => MidiMessage::ProgramChange {
+                channel,
+                program: data(data1)?,
+            },
+            0xd0 => MidiMessage::ChannelPressure {
+                channel,
+                pressure: data(data1)?,
+            },
+            0xe0 => MidiMessage::PitchBend {
+                channel,
+                value: join_14bit(data1, data2)?,
+            },
+            _ => match status {
+                0xf1 => MidiMessage::TimeCodeQuarterFrame(data(data1)?),
+                0xf2 => MidiMessage::SongPosition(join_14bit(data1, data2)?),
+                0xf3 => MidiMessage::SongSelect(data(data1)?),
+                0xf6 => MidiMessage::TuneRequest,
+                0xf8 => MidiMessage::TimingClock,
+                0xfa => MidiMessage::Start,
+                0xfb => MidiMessage::Continue,
+                0xfc => MidiMessage::Stop,
+                0xfe => MidiMessage::ActiveSensing,
+                0xff => MidiMessage::SystemReset,
+                _ => return Err(MidiError::UnsupportedStatus(status)),
+            },
+        };
+        Ok(message)
+    }
+
+    /// Returns the status byte followed by two data bytes, unused data bytes
+    /// are zero.
+    pub fn to_bytes(&self) -> Result<[u8; 3], MidiError> {
+        let channel_message = |status: u8, ch: u8, data1: u8, data2: u8| {
+            Ok([status | channel(ch)?, data(data1)?, data(data2)?])
+        };
+        match *self {
+            MidiMessage::NoteOff {
+                channel,
+                key,
+                velocity,
+            } => channel_message(0x80, channel, key, velocity),
+            MidiMessage::NoteOn {
+                channel,
+                key,
+                velocity,
+            } => channel_message(0x90, channel, key, velocity),
+            MidiMessage::PolyPressure {
+                channel,
+                key,
+                pressure,
+            } => channel_message(0xa0, channel, key, pressure),
+            MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            } => channel_message(0xb0, channel, controller, value),
+            MidiMessage::ProgramChange { channel, program } => {
+                channel_message(0xc0, channel, program, 0)
+            }
+            MidiMessage::ChannelPressure { channel, pressure } => {
+                channel_message(0xd0, channel, pressure, 0)
+            }
+            MidiMessage::PitchBend { channel, value } => {
+                let (lsb, msb) = split_14bit(value)?;
+                channel_message(0xe0, channel, lsb, msb)
+            }
+            MidiMessage::TimeCodeQuarterFrame(value) => Ok([0xf1, data(value)?, 0]),
+            MidiMessage::SongPosition(value) => {
+                let (lsb, msb) = split_14bit(value)?;
+                Ok([0xf2, lsb, msb])
+            }
+            MidiMessage::SongSelect(song) => Ok([0xf3, data(song)?, 0]),
+            MidiMessage::TuneRequest => Ok([0xf6, 0, 0]),
+            MidiMessage::TimingClock => Ok([0xf8, 0, 0]),
+            MidiMessage::Start => Ok([0xfa, 0, 0]),
+            MidiMessage::Continue => Ok([0xfb, 0, 0]),
+            MidiMessage::Stop => Ok([0xfc, 0, 0]),
+            MidiMessage::ActiveSensing => Ok([0xfe, 0, 0]),
+            MidiMessage::SystemReset => Ok([0xff, 0, 0]),
+        }
+    }
+
+    /// Returns the OSC representation of the message for the given port.
+    pub fn to_osc(&self, port: u8) -> Result<OscMidiMessage, MidiError> {
+        let [status, data1, data2] = self.to_bytes()?;
+        Ok(OscMidiMessage {
+            port,
+            status,
+            data1,
+            data2,
+        })
+    }
+
+    /// Returns the zero based channel of channel messages.
+    pub fn channel(&self) -> Option<u8> {
+        match *self {
+            MidiMessage::NoteOff { channel, .. }
+            | MidiMessage::NoteOn { channel, .. }
+            | MidiMessage::PolyPressure { channel, .. }
+            | MidiMessage::ControlChange { channel, .. }
+            | MidiMessage::ProgramChange { channel, .. }
+            | MidiMessage::ChannelPressure { channel, .. }
+            | MidiMessage::PitchBend { channel, .. } => Some(channel),
+            _ => None,
+        }
+    }
+}
+
+impl OscMidiMessage {
+    /// Returns the typed MIDI message, the port is not part of it.
+    pub fn message(&self) -> Result<MidiMessage, MidiError> {
+        MidiMessage::from_bytes([self.status, self.data1, self.data2])
+    }
+}
+
+impl TryFrom<OscMidiMessage> for MidiMessage {
+    type Error = MidiError;
+
+    fn try_from(msg: OscMidiMessage) -> Result<Self, MidiError> {
+        msg.message()
+    }
+}
+
+/// Converts the message for port 0.
+impl TryFrom<MidiMessage> for OscMidiMessage {
+    type Error = MidiError;
+
+    fn try_from(msg: MidiMessage) -> Result<Self, MidiError> {
+        msg.to_osc(0)
+    }
+}
@@ -0,0 +1,419 @@
+use super::{encode_path, json_to_value, read_line, split_type_tags};
+use super::{Access, HostInfo, Method, OscQueryError, Range};
+use crate::types::{OscMessage, OscType};
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Maximum size of a response body.
+const MAX_BODY: u64 = 64 * 1024 * 1024;
+
+/// A client fetching the address space of a remote OSCQuery server.
+///
+/// # Example
+///
+/// ```no_run
+/// use rosc::oscquery::Client;
+/// use rosc::OscType;
+///
+/// let client = Client::new("127.0.0.1:8080").unwrap();
+/// let tree = client.node("/").unwrap();
+/// let gain = tree.find("/mixer/1/gain").unwrap();
+/// let msg = gain.message().arg(OscType::Float(0.5)).build().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Client {
+    addr: SocketAddr,
+    timeout: Option<Duration>,
+}
+
+impl Client {
+    /// Creates a client for the OSCQuery server at `addr`.
+    pub fn new<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let addr = addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "address resolved to nothing")
+        })?;
+        Ok(Client {
+            addr,
+            timeout: None,
+        })
+    }
+
+    /// Sets the timeout for connecting and for each read and write.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fetches the node at `path` including all of its children.
+    pub fn node(&self, path: &str) -> Result<Node, OscQueryError> {
+        match self.get(&encode_path(path))? {
+            Some(node) => Node::from_json(&node),
+            None => Err(OscQueryError::BadResponse("missing body")),
+        }
+    }
+
+    /// Fetches the `HOST_INFO` of the server.
+    pub fn host_info(&self) -> Result<HostInfo, OscQueryError> {
+        match self.get("/?HOST_INFO")? {
+            Some(host_info) => HostInfo::from_json(&host_info),
+            None => Err(OscQueryError::BadResponse("missing body")),
+        }
+    }
+
+    /// Fetches a single attribute of the node at `path`, e.g. `"VALUE"`.
+    /// Returns `None` if the node doesn't have the attribute.
+    pub fn attribute(&self, path: &str, attribute: &str) -> Result<Option<Value>, OscQueryError> {
+        let response = self.get(&format!("{}?{}", encode_path(path), attribute))?;
+        Ok(response.and_then(|mut response| {
+            response
+                .as_object_mut()
+                .and_then(|response| response.remove(attribute))
+        }))
+    }
+
+    /// Sends a GET request, returns `None` for responses without content.
+    fn get(&self, target: &str) -> Result<Option<Value>, OscQueryError> {
+        let stream = match self.timeout {
+            Some(timeout) => TcpStream::connect_timeout(&self.addr, timeout)?,
+            None => TcpStream::connect(self.addr)?,
+        };
+        stream.set_read_timeout(self.timeout)?;
+        stream.set_write_timeout(self.timeout)?;
+        write!(
+            &stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nAccept: application/json\r\nConnection: close\r\n\r\n",
+            target, self.addr
+        )?;
+
+        let mut reader = BufReader::new(&stream);
+        let status = read_status(&mut reader)?;
+        let mut content_length = None;
+        let mut chunked = false;
+        loop {
+            let line =
+                read_line(&mut reader)?.ok_or(OscQueryError::BadResponse("truncated headers"))?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                let value = value.trim();
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = Some(
+                        value
+                            .parse::<u64>()
+                            .map_err(|_| OscQueryError::BadResponse("invalid Content-Length"))?,
+                    );
+                } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                    chunked = value.eq_ignore_ascii_case("chunked");
+                }
+            }
+        }
+
+        match status {
+            200 => {}
+            204 => return Ok(None),
+            status => return Err(OscQueryError::Http(status)),
+        }
+        let body = if chunked {
+            read_chunked(&mut reader)?
+        } else {
+            let mut body = Vec::new();
+            let limit = content_length.unwrap_or(MAX_BODY).min(MAX_BODY);
+            reader.take(limit).read_to_end(&mut body)?;
+            if content_length.map_or(false, |length| length != body.len() as u64) {
+                return Err(OscQueryError::BadResponse("truncated body"));
+            }
+            body
+        };
+        if body.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&body)?))
+    }
+}
+
+/// Reads the status line, returns the status code.
+fn read_status<R: BufRead>(reader: &mut R) -> Result<u16, OscQueryError> {
+    let line = read_line(reader)?.ok_or(OscQueryError::BadResponse("missing status line"))?;
+    let mut parts = line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some(version), Some(status)) if version.starts_with("HTTP/") => status
+            .parse()
+            .map_err(|_| OscQueryError::BadResponse("invalid status code")),
+        _ => Err(OscQueryError::BadResponse("invalid status line")),
+    }
+}
+
+/// Reads a body with chunked transfer encoding.
+fn read_chunked<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, OscQueryError> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(reader)?.ok_or(OscQueryError::BadResponse("truncated chunk"))?;
+        // Chunk extensions are ignored
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = u64::from_str_radix(size, 16)
+            .map_err(|_| OscQueryError::BadResponse("invalid chunk size"))?;
+        if size == 0 {
+            break;
+        }
+        if body.len() as u64 + size > MAX_BODY {
+            return Err(OscQueryError::BadResponse("body too large"));
+        }
+        let start = body.len();
+        (&mut *reader).take(size).read_to_end(&mut body)?;
+        if ((body.len() - start) as u64) < size {
+            return Err(OscQueryError::BadResponse("truncated chunk"));
+        }
+        // The CRLF following the chunk data
+        if read_line(reader)? != Some(String::new()) {
+            return Err(OscQueryError::BadResponse("invalid chunk"));
+        }
+    }
+    // Skip the trailer
+    while let Some(line) = read_line(reader)? {
+        if line.is_empty() {
+            break;
+        }
+    }
+    Ok(body)
+}
+
+/// A node of a remote address space.
+///
+/// Nodes with type tags are OSC methods, all other nodes are containers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub full_path: String,
+    /// The method at this node, if the node has a `TYPE`.
+    pub method: Option<Method>,
+    /// The child nodes by name.
+    pub contents: BTreeMap<String, Node>,
+}
+
+impl Node {
+    /// Parses an OSCQuery node including all of its children.
+    pub fn from_json(node: &Value) -> Result<Self, OscQueryError> {
+        let node = node
+            .as_object()
+            .ok_or_else(|| OscQueryError::BadNode("node is not an object".to_string()))?;
+        let full_path = node
+            .get("FULL_PATH")
+            .and_then(Value::as_str)
+            .ok_or_else(|| OscQueryError::BadNode("node has no FULL_PATH".to_string()))?
+            .to_string();
+        let bad = |attribute: &str| {
+            OscQueryError::BadNode(format!("{} of {} is invalid", attribute, full_path))
+        };
+
+        let method = match node.get("TYPE") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(type_tags)) => {
+                let mut method = Method::new(type_tags);
+                let tags = split_type_tags(&method.type_tags);
+                if let Some(access) = node.get("ACCESS") {
+                    method.access = access
+                        .as_u64()
+                        .and_then(Access::from_u64)
+                        .ok_or_else(|| bad("ACCESS"))?;
+                }
+                match node.get("RANGE") {
+                    None | Some(Value::Null) => {}
+                    Some(Value::Array(ranges)) => {
+                        method.ranges = ranges
+                            .iter()
+                            .zip(tags.iter())
+                            .map(|(range, tag)| match range {
+                                Value::Null => Ok(Range::default()),
+                                range => Range::from_json(range, Some(tag)),
+                            })
+                            .collect::<Result<_, _>>()?;
+                    }
+                    Some(_) => return Err(bad("RANGE")),
+                }
+                if let Some(description) = node.get("DESCRIPTION").and_then(Value::as_str) {
+                    method.description = Some(description.to_string());
+                }
+                match node.get("VALUE") {
+                    None | Some(Value::Null) => {}
+                    Some(Value::Array(values)) => {
+                        // Values without an OSC representation are reported as null,
+                        // they become nil to keep the positions of the others
+                        method.value = values
+                            .iter()
+                            .zip(tags.iter())
+                            .map(|(value, tag)| {
+                                json_to_value(value, Some(tag)).unwrap_or(OscType::Nil)
+                            })
+                            .collect();
+                    }
+                    Some(_) => return Err(bad("VALUE")),
+                }
+                Some(method)
+            }
+            Some(_) => return Err(bad("TYPE")),
+        };
+
+        let contents = match node.get("CONTENTS") {
+            None | Some(Value::Null) => BTreeMap::new(),
+            Some(Value::Object(contents)) => contents
+                .iter()
+                .map(|(name, child)| Ok((name.clone(), Node::from_json(child)?)))
+                .collect::<Result<_, OscQueryError>>()?,
+            Some(_) => return Err(bad("CONTENTS")),
+        };
+
+        Ok(Node {
+            full_path,
+            method,
+            contents,
+        })
+    }
+
+    /// Returns the descendant node at the absolute `path`, or this node if
+    /// `path` is its own full path.
+    pub fn find(&self, path: &str) -> Option<&Node> {
+        let own = self.full_path.trim_end_matches('/');
+        let rest = path.trim_end_matches('/').strip_prefix(own)?;
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return None;
+        }
+        rest.split('/')
+            .filter(|part| !part.is_empty())
+            .try_fold(self, |node, part| node.contents.get(part))
+    }
+
+    /// Returns this node and all of its descendants that are methods, in
+    /// address order.
+    pub fn methods(&self) -> Vec<&Node> {
+        let mut methods = Vec::new();
+        self.collect_methods(&mut methods);
+        methods
+    }
+
+    fn collect_methods<'a>(&'a self, methods: &mut Vec<&'a Node>) {
+        if self.method.is_some() {
+            methods.push(self);
+        }
+        for child in self.contents.values() {
+            child.collect_methods(methods);
+        }
+    }
+
+    /// Returns a builder for a message to this node, whose arguments are
+    /// validated against the node's type tags, access mode and ranges.
+    pub fn message(&self) -> MessageBuilder<'_> {
+        MessageBuilder {
+            node: self,
+            args: Vec::new(),
+        }
+    }
+}
+
+/// Builds a message to a [`Node`], see [`Node::message`].
+#[derive(Clone, Debug)]
+pub struct MessageBuilder<'a> {
+    node: &'a Node,
+    args: Vec<OscType>,
+}
+
+impl<'a> MessageBuilder<'a> {
+    /// Appends an argument.
+    pub fn arg<T: Into<OscType>>(mut self, arg: T) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Validates the arguments and returns the message.
+    pub fn build(self) -> Result<OscMessage, OscQueryError> {
+        let method = match &self.node.method {
+            Some(method)
+                if method.access == Access::Write || method.access == Access::ReadWrite =>
+            {
+                method
+            }
+            _ => return Err(OscQueryError::NotWritable(self.node.full_path.clone())),
+        };
+        let tags = split_type_tags(&method.type_tags);
+        if self.args.len() != tags.len() {
+            return Err(OscQueryError::BadArg {
+                index: self.args.len().min(tags.len()),
+                reason: format!("expected {} arguments, got {}", tags.len(), self.args.len()),
+            });
+        }
+        for (index, (arg, tag)) in self.args.iter().zip(tags.iter()).enumerate() {
+            let bad = |reason: String| OscQueryError::BadArg { index, reason };
+            if !has_type(arg, tag) {
+                return Err(bad(format!("expected type {}, got {:?}", tag, arg)));
+            }
+            if let Some(range) = method.ranges.get(index) {
+                check_range(arg, range).map_err(bad)?;
+            }
+        }
+        Ok(OscMessage {
+            addr: self.node.full_path.clone(),
+            args: self.args,
+        })
+    }
+}
+
+/// Whether `arg` can be sent for the type tag `tag`.
+fn has_type(arg: &OscType, tag: &str) -> bool {
+    if let Some(inner) = tag.strip_prefix('[') {
+        let tags = split_type_tags(&inner[..inner.len() - 1]);
+        return match arg {
+            OscType::Array(array) => {
+                array.content.len() == tags.len()
+                    && array
+                        .content
+                        .iter()
+                        .zip(tags.iter())
+                        .all(|(arg, tag)| has_type(arg, tag))
+            }
+            _ => false,
+        };
+    }
+    matches!(
+        (tag, arg),
+        ("i", OscType::Int(_))
+            | ("h", OscType::Long(_))
+            | ("f", OscType::Float(_))
+            | ("d", OscType::Double(_))
+            | ("s", OscType::String(_))
+            | ("S", OscType::String(_))
+            | ("c", OscType::Char(_))
+            | ("b", OscType::Blob(_))
+            | ("t", OscType::Time(_))
+            | ("m", OscType::Midi(_))
+            | ("r", OscType::Color(_))
+            | ("T", OscType::Bool(_))
+            | ("F", OscType::Bool(_))
+            | ("N", OscType::Nil)
+            | ("I", OscType::Inf)
+    )
+}
+
+fn check_range(arg: &OscType, range: &Range) -> Result<(), String> {
+    if !range.vals.is_empty() && !range.vals.contains(arg) {
+        return Err(format!("{:?} is not one of {:?}", arg, range.vals));
+    }
+    let number = match *arg {
+        OscType::Int(v) => f64::from(v),
+        OscType::Long(v) => v as f64,
+        OscType::Float(v) => f64::from(v),
+        OscType::Double(v) => v,
+        _ => return Ok(()),
+    };
+    if range.min.map_or(false, |min| number < min) || range.max.map_or(false, |max| number > max) {
+        return Err(format!(
+            "{} is out of range {}..={}",
+            number,
+            range.min.map_or(String::new(), |min| min.to_string()),
+            range.max.map_or(String::new(), |max| max.to_string())
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,603 @@
+//! [OSCQuery](https://github.com/Vidvox/OSCQueryProposal) lets controllers
+//! discover the OSC address space of a device over HTTP. The address space
+//! is described as a tree of JSON nodes listing each method's type tags,
+//! access mode, value ranges, description and current values. Clients can
+//! subscribe to value changes of single methods over a websocket on the
+//! same port.
+
+use crate::address::OscAddress;
+use crate::errors::OscError;
+use crate::types::OscType;
+
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+use std::io::BufRead;
+use std::net::IpAddr;
+use std::{error, io};
+
+mod client;
+mod server;
+mod websocket;
+
+pub use self::client::{Client, MessageBuilder, Node};
+pub use self::server::{Server, Updater};
+pub use self::websocket::{Command, WebSocket, WsMessage};
+
+/// Errors returned by the OSCQuery client and websocket.
+#[derive(Debug)]
+pub enum OscQueryError {
+    /// The connection to the server failed.
+    Io(io::Error),
+    /// The server answered with an unexpected HTTP status code.
+    Http(u16),
+    /// The response is not valid HTTP.
+    BadResponse(&'static str),
+    /// The response body is not valid JSON.
+    Json(serde_json::Error),
+    /// The JSON does not describe a valid OSCQuery node or host info.
+    BadNode(String),
+    /// The node can't receive messages, since it has no type tags or is
+    /// not writable.
+    NotWritable(String),
+    /// An argument doesn't match the node's type tags or ranges.
+    BadArg { index: usize, reason: String },
+    /// The websocket handshake failed or a frame violates the protocol.
+    WebSocket(&'static str),
+    /// A binary websocket frame is not a valid OSC packet.
+    Osc(OscError),
+}
+
+impl Display for OscQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OscQueryError::Io(err) => write!(f, "OSCQuery I/O error: {}", err),
+            OscQueryError::Http(status) => write!(f, "OSCQuery server returned HTTP {}", status),
+            OscQueryError::BadResponse(msg) => write!(f, "bad HTTP response: {}", msg),
+            OscQueryError::Json(err) => write!(f, "bad OSCQuery JSON: {}", err),
+            OscQueryError::BadNode(msg) => write!(f, "bad OSCQuery node: {}", msg),
+            OscQueryError::NotWritable(path) => write!(f, "{} is not writable", path),
+            OscQueryError::BadArg { index, reason } => {
+                write!(f, "bad argument at index {}: {}", index, reason)
+            }
+            OscQueryError::WebSocket(msg) => write!(f, "websocket error: {}", msg),
+            OscQueryError::Osc(err) => write!(f, "bad OSC packet: {}", err),
+        }
+    }
+}
+
+impl error::Error for OscQueryError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            OscQueryError::Io(err) => Some(err),
+            OscQueryError::Json(err) => Some(err),
+            OscQueryError::Osc(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for OscQueryError {
+    fn from(err: io::Error) -> Self {
+        OscQueryError::Io(err)
+    }
+}
+
+impl From<OscError> for OscQueryError {
+    fn from(err: OscError) -> Self {
+        OscQueryError::Osc(err)
+    }
+}
+
+impl From<serde_json::Error> for OscQueryError {
+    fn from(err: serde_json::Error) -> Self {
+        OscQueryError::Json(err)
+    }
+}
+
+/// Whether the value of a method can be read, written or both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    None = 0,
+    Read = 1,
+    Write = 2,
+    ReadWrite = 3,
+}
+
+impl Access {
+    /// Returns the access mode for the numeric `ACCESS` attribute.
+    pub fn from_u64(access: u64) -> Option<Self> {
+        match access {
+            0 => Some(Access::None),
+            1 => Some(Access::Read),
+            2 => Some(Access::Write),
+            3 => Some(Access::ReadWrite),
+            _ => None,
+        }
+    }
+}
+
+/// The range of values accepted for one argument of a method.
+///
+/// A range is either bounded by a minimum and/or maximum, or it enumerates
+/// all allowed values.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Range {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub vals: Vec<OscType>,
+}
+
+impl Range {
+    /// A range from `min` to `max`, inclusive.
+    pub fn new(min: f64, max: f64) -> Self {
+        Range {
+            min: Some(min),
+            max: Some(max),
+            vals: Vec::new(),
+        }
+    }
+
+    /// A range consisting of the given values.
+    pub fn values<I: IntoIterator<Item = OscType>>(vals: I) -> Self {
+        Range {
+            min: None,
+            max: None,
+            vals: vals.into_iter().collect(),
+        }
+    }
+
+    /// Parses a `RANGE` entry, `type_tag` is used to convert the `VALS`.
+    fn from_json(range: &Value, type_tag: Option<&str>) -> Result<Self, OscQueryError> {
+        let range = range
+            .as_object()
+            .ok_or_else(|| OscQueryError::BadNode("RANGE entry is not an object".to_string()))?;
+        let bound = |key| match range.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(v) => v
+                .as_f64()
+                .map(Some)
+                .ok_or_else(|| OscQueryError::BadNode(format!("{} of RANGE is not a number", key))),
+        };
+        let vals = match range.get("VALS") {
+            Some(Value::Array(vals)) => vals
+                .iter()
+                .filter_map(|v| json_to_value(v, type_tag))
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(Range {
+            min: bound("MIN")?,
+            max: bound("MAX")?,
+            vals,
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        let mut range = Map::new();
+        if let Some(min) = self.min {
+            range.insert("MIN".to_string(), json!(min));
+        }
+        if let Some(max) = self.max {
+            range.insert("MAX".to_string(), json!(max));
+        }
+        if !self.vals.is_empty() {
+            range.insert(
+                "VALS".to_string(),
+                self.vals.iter().map(value_to_json).collect(),
+            );
+        }
+        Value::Object(range)
+    }
+}
+
+/// Describes an OSC method of an [`AddressSpace`].
+///
+/// # Example
+///
+/// ```
+/// use rosc::oscquery::{Access, Method, Range};
+/// use rosc::OscType;
+///
+/// let gain = Method::new("f")
+///     .access(Access::ReadWrite)
+///     .range(Range::new(0.0, 1.0))
+///     .description("Channel gain")
+///     .value(vec![OscType::Float(0.5)]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Method {
+    /// The type tags of the method's arguments, without the leading `,`.
+    pub type_tags: String,
+    pub access: Access,
+    /// One range per argument.
+    pub ranges: Vec<Range>,
+    pub description: Option<String>,
+    /// The current values, one per argument.
+    pub value: Vec<OscType>,
+}
+
+impl Method {
+    /// Creates a writable method with the given type tags, e.g. `"ff"`.
+    pub fn new(type_tags: &str) -> Self {
+        Method {
+            type_tags: type_tags.trim_start_matches(',').to_string(),
+            access: Access::Write,
+            ranges: Vec::new(),
+            description: None,
+            value: Vec::new(),
+        }
+    }
+
+    /// Sets the access mode.
+    pub fn access(mut self, access: Access) -> Self {
+        self.access = access;
+        self
+    }
+
+    /// Adds the range of the next argument.
+    pub fn range(mut self, range: Range) -> Self {
+        self.ranges.push(range);
+        self
+    }
+
+    /// Sets the human readable description.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the current values.
+    pub fn value(mut self, value: Vec<OscType>) -> Self {
+        self.value = value;
+        self
+    }
+
+    fn add_attributes(&self, node: &mut Map<String, Value>) {
+        node.insert("TYPE".to_string(), json!(self.type_tags));
+        node.insert("ACCESS".to_string(), json!(self.access as u8));
+        if !self.ranges.is_empty() {
+            node.insert(
+                "RANGE".to_string(),
+                self.ranges.iter().map(Range::to_json).collect(),
+            );
+        }
+        if let Some(description) = &self.description {
+            node.insert("DESCRIPTION".to_string(), json!(description));
+        }
+        // Write-only values must not be reported
+        if !self.value.is_empty() && self.access != Access::Write {
+            node.insert(
+                "VALUE".to_string(),
+                self.value.iter().map(value_to_json).collect(),
+            );
+        }
+    }
+}
+
+/// The OSC methods of a device, served as a tree of OSCQuery nodes.
+///
+/// Container nodes are derived from the method addresses, e.g. adding
+/// `/mixer/1/gain` creates the nodes `/mixer` and `/mixer/1`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AddressSpace {
+    methods: BTreeMap<String, Method>,
+}
+
+impl AddressSpace {
+    /// Creates an empty address space.
+    pub fn new() -> Self {
+        AddressSpace::default()
+    }
+
+    /// Adds a method, replacing any previous method with the same address.
+    pub fn add(&mut self, address: OscAddress, method: Method) -> &mut Self {
+        self.methods.insert(address.as_str().to_string(), method);
+        self
+    }
+
+    /// Removes a method and returns it.
+    pub fn remove(&mut self, address: &str) -> Option<Method> {
+        self.methods.remove(address)
+    }
+
+    /// Returns the method at `address`.
+    pub fn get(&self, address: &str) -> Option<&Method> {
+        self.methods.get(address)
+    }
+
+    /// Updates the current values of a method, returns `false` if there is
+    /// no method at `address`.
+    pub fn set_value(&mut self, address: &str, value: Vec<OscType>) -> bool {
+        match self.methods.get_mut(address) {
+            Some(method) => {
+                method.value = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Iterates over all methods and their addresses in lexical order.
+    pub fn methods(&self) -> impl Iterator<Item = (&str, &Method)> {
+        self.methods
+            .iter()
+            .map(|(addr, method)| (addr.as_str(), method))
+    }
+
+    /// Returns the OSCQuery JSON of the node at `path`, or `None` if no such
+    /// node exists.
+    pub fn node_json(&self, path: &str) -> Option<Value> {
+        let path = match path.trim_end_matches('/') {
+            "" => "/",
+            path => path,
+        };
+        let prefix = if path == "/" {
+            "/".to_string()
+        } else {
+            format!("{}/", path)
+        };
+
+        let mut node = Map::new();
+        node.insert("FULL_PATH".to_string(), json!(path));
+        if let Some(method) = self.methods.get(path) {
+            method.add_attributes(&mut node);
+        }
+
+        let children: BTreeSet<&str> = self
+            .methods
+            .keys()
+            .filter_map(|addr| addr.strip_prefix(prefix.as_str()))
+            .map(|rest| rest.split('/').next().unwrap_or(rest))
+            .collect();
+        if children.is_empty() && !self.methods.contains_key(path) && path != "/" {
+            return None;
+        }
+        if !children.is_empty() {
+            let contents = children
+                .into_iter()
+                .filter_map(|child| {
+                    let child_path = format!("{}{}", prefix, child);
+                    Some((child.to_string(), self.node_json(&child_path)?))
+                })
+                .collect();
+            node.insert("CONTENTS".to_string(), Value::Object(contents));
+        } else if path == "/" {
+            node.insert("CONTENTS".to_string(), json!({}));
+        }
+        if !self.methods.contains_key(path) {
+            node.insert("ACCESS".to_string(), json!(Access::None as u8));
+        }
+        Some(Value::Object(node))
+    }
+}
+
+/// The OSC transport protocol of a host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transport {
+    Udp,
+    Tcp,
+}
+
+/// Information about the OSC server of a host, returned for `?HOST_INFO`
+/// queries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostInfo {
+    pub name: String,
+    /// The IP address of the OSC server, defaults to the address of the
+    /// OSCQuery server if not set.
+    pub osc_ip: Option<IpAddr>,
+    pub osc_port: u16,
+    pub osc_transport: Transport,
+}
+
+impl HostInfo {
+    /// Creates the host info for an OSC server receiving UDP on `osc_port`.
+    pub fn new(name: &str, osc_port: u16) -> Self {
+        HostInfo {
+            name: name.to_string(),
+            osc_ip: None,
+            osc_port,
+            osc_transport: Transport::Udp,
+        }
+    }
+
+    /// Parses a `HOST_INFO` response.
+    fn from_json(host_info: &Value) -> Result<Self, OscQueryError> {
+        let bad = |msg: &str| OscQueryError::BadNode(format!("HOST_INFO {}", msg));
+        let osc_ip = match host_info.get("OSC_IP").and_then(Value::as_str) {
+            Some(ip) => Some(ip.parse().map_err(|_| bad("has an invalid OSC_IP"))?),
+            None => None,
+        };
+        let osc_transport = match host_info.get("OSC_TRANSPORT").and_then(Value::as_str) {
+            Some("TCP") => Transport::Tcp,
+            Some("UDP") | None => Transport::Udp,
+            Some(_) => return Err(bad("has an unknown OSC_TRANSPORT")),
+        };
+        Ok(HostInfo {
+            name: host_info
+                .get("NAME")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            osc_ip,
+            osc_port: host_info
+                .get("OSC_PORT")
+                .and_then(Value::as_u64)
+                .and_then(|port| u16::try_from(port).ok())
+                .ok_or_else(|| bad("has no valid OSC_PORT"))?,
+            osc_transport,
+        })
+    }
+
+    /// Returns the `HOST_INFO` JSON, `local_ip` is used if no OSC IP is set.
+    fn to_json(&self, local_ip: IpAddr) -> Value {
+        json!({
+            "NAME": self.name,
+            "OSC_IP": self.osc_ip.unwrap_or(local_ip).to_string(),
+            "OSC_PORT": self.osc_port,
+            "OSC_TRANSPORT": match self.osc_transport {
+                Transport::Udp => "UDP",
+                Transport::Tcp => "TCP",
+            },
+            "EXTENSIONS": {
+                "ACCESS": true,
+                "VALUE": true,
+                "RANGE": true,
+                "DESCRIPTION": true,
+                "LISTEN": true,
+            },
+        })
+    }
+}
+
+/// Maximum length of HTTP request, status and header lines.
+const MAX_LINE: usize = 8 * 1024;
+
+/// Reads a CRLF terminated line, returns `None` at the end of the stream or
+/// if the line is too long.
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let read = io::Read::take(&mut *reader, MAX_LINE as u64).read_until(b'\n', &mut line)?;
+    if read == 0 || line.last() != Some(&b'\n') {
+        return Ok(None);
+    }
+    let line = String::from_utf8_lossy(&line);
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+/// Percent-encodes the segments of an OSC address for a request target,
+/// keeping the `/` separators.
+pub(crate) fn encode_path(path: &str) -> String {
+    let mut target = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'/' | b'-' | b'.' | b'_' | b'~' => target.push(char::from(byte)),
+            byte if byte.is_ascii_alphanumeric() => target.push(char::from(byte)),
+            byte => target.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    target
+}
+
+/// Decodes the percent-encoded path of a request target, returns `None` for
+/// invalid escapes or UTF-8.
+pub(crate) fn decode_path(target: &str) -> Option<String> {
+    let mut path = Vec::with_capacity(target.len());
+    let mut rest = target.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match (byte, tail) {
+            (b'%', [high, low, tail @ ..]) => {
+                let digit = |c: &u8| char::from(*c).to_digit(16);
+                path.push((digit(high)? << 4 | digit(low)?) as u8);
+                rest = tail;
+            }
+            (b'%', _) => return None,
+            (byte, tail) => {
+                path.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(path).ok()
+}
+
+/// Converts an OSC value to its OSCQuery JSON representation.
+///
+/// Colors are represented as `#RRGGBBAA` strings, booleans as JSON booleans
+/// and values without a JSON representation (nil, infinitum, blobs, MIDI
+/// messages, time tags and unknown types) as `null`.
+pub(crate) fn value_to_json(value: &OscType) -> Value {
+    match value {
+        OscType::Int(v) => json!(v),
+        OscType::Long(v) => json!(v),
+        OscType::Float(v) => json!(v),
+        OscType::Double(v) => json!(v),
+        OscType::String(v) => json!(v),
+        OscType::Char(v) => json!(v.to_string()),
+        OscType::Bool(v) => json!(v),
+        OscType::Color(v) => json!(v.to_hex()),
+        OscType::Array(v) => v.content.iter().map(value_to_json).collect(),
+        OscType::Blob(_)
+        | OscType::Time(_)
+        | OscType::Midi(_)
+        | OscType::Nil
+        | OscType::Inf
+        | OscType::Unknown { .. } => Value::Null,
+    }
+}
+
+/// Converts an OSCQuery JSON value to an OSC value of the given type tag.
+///
+/// Without a type tag the OSC type is derived from the JSON type. Returns
+/// `None` if the value can't be represented with the type tag.
+pub(crate) fn json_to_value(value: &Value, type_tag: Option<&str>) -> Option<OscType> {
+    let tag = match type_tag {
+        Some(tag) if tag.starts_with('[') => {
+            // The value of an array type tag is a JSON array
+            let inner = &tag[1..tag.len() - 1];
+            let tags = split_type_tags(inner);
+            return match value {
+                Value::Array(values) if values.len() == tags.len() => values
+                    .iter()
+                    .zip(tags.iter())
+                    .map(|(v, t)| json_to_value(v, Some(t)))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|content| OscType::Array(content.into_iter().collect())),
+                _ => None,
+            };
+        }
+        Some(tag) => tag.chars().next(),
+        None => None,
+    };
+    match (tag, value) {
+        (Some('i'), v) => v
+            .as_i64()
+            .and_then(|v| i32::try_from(v).ok())
+            .map(OscType::Int),
+        (Some('h'), v) => v.as_i64().map(OscType::Long),
+        (Some('f'), v) => v.as_f64().map(|v| OscType::Float(v as f32)),
+        (Some('d'), v) => v.as_f64().map(OscType::Double),
+        (Some('s'), Value::String(v)) | (Some('S'), Value::String(v)) => {
+            Some(OscType::String(v.clone()))
+        }
+        (Some('c'), Value::String(v)) => v.chars().next().map(OscType::Char),
+        (Some('T'), v) | (Some('F'), v) => v.as_bool().map(OscType::Bool),
+        (Some('r'), Value::String(v)) => v.parse().ok().map(OscType::Color),
+        (Some('N'), _) => Some(OscType::Nil),
+        (Some('I'), _) => Some(OscType::Inf),
+        (Some(_), _) => None,
+        (None, Value::Bool(v)) => Some(OscType::Bool(*v)),
+        (None, Value::String(v)) => Some(OscType::String(v.clone())),
+        (None, Value::Number(v)) => match v.as_i64() {
+            Some(v) => Some(i32::try_from(v).map_or(OscType::Long(v), OscType::Int)),
+            None => v.as_f64().map(OscType::Double),
+        },
+        (None, Value::Array(values)) => values
+            .iter()
+            .map(|v| json_to_value(v, None))
+            .collect::<Option<Vec<_>>>()
+            .map(|content| OscType::Array(content.into_iter().collect())),
+        (None, _) => None,
+    }
+}
+
+/// Splits a type tag string into the tags of the individual arguments,
+/// keeping arrays like `[ff]` together.
+pub(crate) fn split_type_tags(type_tags: &str) -> Vec<&str> {
+    let mut tags = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in type_tags.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            tags.push(&type_tags[start..i + c.len_utf8()]);
+            start = i + c.len_utf8();
+        }
+    }
+    tags
+}
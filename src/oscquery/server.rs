@@ -0,0 +1,420 @@
+use super::websocket::{self, Command, Incoming, WsMessage};
+use super::{decode_path, read_line, Access, AddressSpace, HostInfo, OscQueryError};
+use crate::types::{OscMessage, OscPacket, OscType};
+
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::thread;
+use std::time::Duration;
+
+/// How long streaming a value to a websocket client may block.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a client may take to send its request, idle connections would
+/// occupy a thread forever otherwise.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A minimal HTTP server answering OSCQuery requests for an [`AddressSpace`].
+///
+/// `GET /some/path` returns the JSON of the node at `/some/path` including
+/// all of its children, `GET /some/path?VALUE` returns a single attribute of
+/// the node and `GET /?HOST_INFO` returns the [`HostInfo`].
+///
+/// Clients can upgrade a connection to a websocket and send `LISTEN` and
+/// `IGNORE` [`Command`]s to subscribe to the values of single methods. Values
+/// changed through an [`Updater`] are then streamed to them as OSC messages
+/// in binary frames.
+///
+/// # Example
+///
+/// ```no_run
+/// use rosc::address::OscAddress;
+/// use rosc::oscquery::{AddressSpace, HostInfo, Method, Server};
+///
+/// let mut space = AddressSpace::new();
+/// space.add(OscAddress::new("/tempo".to_string()).unwrap(), Method::new("f"));
+///
+/// let server = Server::bind("0.0.0.0:8080", HostInfo::new("synth", 9000), space).unwrap();
+/// server.serve().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    space: Arc<RwLock<AddressSpace>>,
+    host_info: Arc<HostInfo>,
+    listeners: Arc<Listeners>,
+}
+
+impl Server {
+    /// Binds the HTTP server to `addr`.
+    pub fn bind<A: ToSocketAddrs>(
+        addr: A,
+        host_info: HostInfo,
+        space: AddressSpace,
+    ) -> io::Result<Self> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            space: Arc::new(RwLock::new(space)),
+            host_info: Arc::new(host_info),
+            listeners: Arc::default(),
+        })
+    }
+
+    /// Returns the address the server is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Returns a handle to the served address space, which can be used to
+    /// update methods and values while the server is running.
+    pub fn address_space(&self) -> Arc<RwLock<AddressSpace>> {
+        Arc::clone(&self.space)
+    }
+
+    /// Returns a handle which updates values and streams them to the
+    /// websocket clients listening to them.
+    pub fn updater(&self) -> Updater {
+        Updater {
+            space: Arc::clone(&self.space),
+            listeners: Arc::clone(&self.listeners),
+        }
+    }
+
+    /// Accepts connections until an I/O error occurs, every connection is
+    /// served on its own thread.
+    pub fn serve(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let space = Arc::clone(&self.space);
+            let host_info = Arc::clone(&self.host_info);
+            let listeners = Arc::clone(&self.listeners);
+            thread::spawn(move || handle_connection(stream, &space, &host_info, &listeners));
+        }
+        Ok(())
+    }
+}
+
+/// Updates the values of an [`AddressSpace`] served by a [`Server`] and
+/// streams them to listening websocket clients, see [`Server::updater`].
+#[derive(Clone, Debug)]
+pub struct Updater {
+    space: Arc<RwLock<AddressSpace>>,
+    listeners: Arc<Listeners>,
+}
+
+impl Updater {
+    /// Sets the current values of the method at `address` and sends them to
+    /// all clients listening to it. Values of write-only methods are not
+    /// streamed. Returns `false` if there is no such method.
+    pub fn set_value(&self, address: &str, value: Vec<OscType>) -> bool {
+        let readable = {
+            let mut space = match self.space.write() {
+                Ok(space) => space,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            if !space.set_value(address, value.clone()) {
+                return false;
+            }
+            space
+                .get(address)
+                .map_or(false, |method| method.access != Access::Write)
+        };
+        if readable {
+            let packet = OscPacket::Message(OscMessage {
+                addr: address.to_string(),
+                args: value,
+            });
+            self.listeners.send(address, &packet);
+        }
+        true
+    }
+}
+
+/// The websocket clients of a server and the paths they listen to.
+#[derive(Debug, Default)]
+struct Listeners {
+    next_id: AtomicU64,
+    clients: Mutex<BTreeMap<u64, Listener>>,
+}
+
+#[derive(Debug)]
+struct Listener {
+    /// Locked separately, so a client blocking a write doesn't block the
+    /// others.
+    stream: Arc<Mutex<TcpStream>>,
+    paths: BTreeSet<String>,
+}
+
+impl Listeners {
+    fn add(&self, stream: TcpStream) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.lock().insert(
+            id,
+            Listener {
+                stream: Arc::new(Mutex::new(stream)),
+                paths: BTreeSet::new(),
+            },
+        );
+        id
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<u64, Listener>> {
+        lock(&self.clients)
+    }
+
+    /// Sends `packet` to all clients listening to `path`, clients that can't
+    /// be written to are dropped.
+    fn send(&self, path: &str, packet: &OscPacket) {
+        let bytes = match crate::encoder::encode(packet) {
+            Ok(bytes) => bytes,
+            Err(_) => return,
+        };
+        // Writes may block, so they happen without holding the clients lock
+        let streams = self
+            .lock()
+            .iter()
+            .filter(|(_, listener)| listener.paths.contains(path))
+            .map(|(&id, listener)| (id, Arc::clone(&listener.stream)))
+            .collect::<Vec<_>>();
+        let failed = streams
+            .into_iter()
+            .filter(|(_, stream)| {
+                websocket::write_frame(&mut *lock(stream), websocket::OP_BINARY, &bytes, None)
+                    .is_err()
+            })
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        if !failed.is_empty() {
+            let mut clients = self.lock();
+            for id in failed {
+                clients.remove(&id);
+            }
+        }
+    }
+
+    /// Writes a control frame to the client, if it's still connected.
+    fn reply(&self, id: u64, opcode: u8, payload: &[u8]) -> io::Result<()> {
+        let stream = match self.lock().get(&id) {
+            Some(listener) => Arc::clone(&listener.stream),
+            None => return Ok(()),
+        };
+        let mut stream = lock(&stream);
+        websocket::write_frame(&mut *stream, opcode, payload, None)
+    }
+}
+
+/// Locks the `mutex`, ignoring poisoning by a panicking thread.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// An HTTP response status.
+#[derive(Clone, Copy)]
+enum Status {
+    Ok,
+    NoContent,
+    BadRequest,
+    NotFound,
+    MethodNotAllowed,
+}
+
+impl Status {
+    fn line(self) -> &'static str {
+        match self {
+            Status::Ok => "200 OK",
+            Status::NoContent => "204 No Content",
+            Status::BadRequest => "400 Bad Request",
+            Status::NotFound => "404 Not Found",
+            Status::MethodNotAllowed => "405 Method Not Allowed",
+        }
+    }
+}
+
+/// A parsed HTTP request, only the headers needed by the server are kept.
+struct Request {
+    method: String,
+    target: String,
+    // The `Sec-WebSocket-Key` of a websocket upgrade request
+    websocket_key: Option<String>,
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    space: &RwLock<AddressSpace>,
+    host_info: &HostInfo,
+    listeners: &Listeners,
+) {
+    let local_ip = match stream.local_addr() {
+        Ok(addr) => addr.ip(),
+        Err(_) => return,
+    };
+    if stream.set_read_timeout(Some(REQUEST_TIMEOUT)).is_err() {
+        return;
+    }
+    let mut reader = BufReader::new(&stream);
+    let (status, body) = match read_request(&mut reader) {
+        Ok(Some(request)) if request.method == "GET" => match request.websocket_key {
+            Some(key) => {
+                // Errors end the websocket session, the client is gone anyway
+                let _ = serve_websocket(&stream, reader, &key, listeners);
+                return;
+            }
+            None => respond(&request.target, space, host_info, local_ip),
+        },
+        Ok(Some(_)) => (Status::MethodNotAllowed, None),
+        Ok(None) => (Status::BadRequest, None),
+        Err(_) => return,
+    };
+    // The client may already be gone, there is nobody to report the error to
+    let _ = write_response(&stream, status, body);
+}
+
+/// Reads the request line and headers.
+fn read_request<R: BufRead>(reader: &mut R) -> io::Result<Option<Request>> {
+    let request_line = match read_line(reader)? {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+    // No request body is expected
+    let mut upgrade = false;
+    let mut websocket_key = None;
+    loop {
+        match read_line(reader)? {
+            Some(line) if !line.is_empty() => {
+                if let Some((name, value)) = line.split_once(':') {
+                    let value = value.trim();
+                    if name.eq_ignore_ascii_case("upgrade") {
+                        upgrade = value.eq_ignore_ascii_case("websocket");
+                    } else if name.eq_ignore_ascii_case("sec-websocket-key") {
+                        websocket_key = Some(value.to_string());
+                    }
+                }
+            }
+            _ => break,
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/") => {
+            Ok(Some(Request {
+                method: method.to_string(),
+                target: target.to_string(),
+                websocket_key: websocket_key.filter(|_| upgrade),
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Completes the websocket handshake and handles commands until the client
+/// disconnects.
+fn serve_websocket<R: io::Read>(
+    mut stream: &TcpStream,
+    mut reader: R,
+    key: &str,
+    listeners: &Listeners,
+) -> Result<(), OscQueryError> {
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        websocket::accept_key(key)
+    )?;
+    // Websocket clients may stay silent for as long as they like
+    stream.set_read_timeout(None)?;
+    let writer = stream.try_clone()?;
+    writer.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let id = listeners.add(writer);
+    let result = handle_commands(&mut reader, id, listeners);
+    listeners.lock().remove(&id);
+    result
+}
+
+fn handle_commands<R: io::Read>(
+    reader: &mut R,
+    id: u64,
+    listeners: &Listeners,
+) -> Result<(), OscQueryError> {
+    loop {
+        match websocket::read_message(reader)? {
+            Incoming::Message(WsMessage::Command(command)) => {
+                if let Some(listener) = listeners.lock().get_mut(&id) {
+                    match command {
+                        Command::Listen(path) => listener.paths.insert(path),
+                        Command::Ignore(path) => listener.paths.remove(&path),
+                    };
+                }
+            }
+            // Setting values over the websocket is not supported
+            Incoming::Message(WsMessage::Packet(_)) => {}
+            Incoming::Ping(payload) => listeners.reply(id, websocket::OP_PONG, &payload)?,
+            Incoming::Close => {
+                listeners.reply(id, websocket::OP_CLOSE, &[])?;
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn respond(
+    target: &str,
+    space: &RwLock<AddressSpace>,
+    host_info: &HostInfo,
+    local_ip: std::net::IpAddr,
+) -> (Status, Option<Value>) {
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (target, None),
+    };
+    if query == Some("HOST_INFO") {
+        return (Status::Ok, Some(host_info.to_json(local_ip)));
+    }
+    let path = match decode_path(path) {
+        Some(path) => path,
+        None => return (Status::BadRequest, None),
+    };
+
+    let space = match space.read() {
+        Ok(space) => space,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let node = match space.node_json(&path) {
+        Some(node) => node,
+        None => return (Status::NotFound, None),
+    };
+    match query {
+        None | Some("") => (Status::Ok, Some(node)),
+        Some(attribute) => match node.get(attribute) {
+            Some(value) => {
+                let mut response = serde_json::Map::new();
+                response.insert(attribute.to_string(), value.clone());
+                (Status::Ok, Some(Value::Object(response)))
+            }
+            None => (Status::NoContent, None),
+        },
+    }
+}
+
+fn write_response(mut stream: &TcpStream, status: Status, body: Option<Value>) -> io::Result<()> {
+    let body = body.map(|body| body.to_string()).unwrap_or_default();
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+        status.line(),
+        body.len()
+    );
+    if !body.is_empty() {
+        response.push_str("Content-Type: application/json\r\n");
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
@@ -0,0 +1,337 @@
+use super::{read_line, OscQueryError};
+use crate::decoder;
+use crate::encoder;
+use crate::types::OscPacket;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+/// The GUID appended to the handshake key, see RFC 6455 section 1.3.
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Maximum size of a message, including all of its fragments.
+const MAX_MESSAGE: usize = 16 * 1024 * 1024;
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+pub(crate) const OP_BINARY: u8 = 0x2;
+pub(crate) const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+pub(crate) const OP_PONG: u8 = 0xa;
+
+/// A command sent by a client to subscribe to value changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Start streaming the values of the method at the given path.
+    Listen(String),
+    /// Stop streaming the values of the method at the given path.
+    Ignore(String),
+}
+
+impl Command {
+    fn to_json(&self) -> Value {
+        match self {
+            Command::Listen(path) => json!({"COMMAND": "LISTEN", "DATA": path}),
+            Command::Ignore(path) => json!({"COMMAND": "IGNORE", "DATA": path}),
+        }
+    }
+
+    fn from_json(command: &Value) -> Option<Self> {
+        let path = command.get("DATA")?.as_str()?.to_string();
+        match command.get("COMMAND")?.as_str()? {
+            "LISTEN" => Some(Command::Listen(path)),
+            "IGNORE" => Some(Command::Ignore(path)),
+            _ => None,
+        }
+    }
+}
+
+/// A message received over an OSCQuery websocket.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WsMessage {
+    /// An OSC packet, sent as a binary frame.
+    Packet(OscPacket),
+    /// A command, sent as a JSON text frame.
+    Command(Command),
+}
+
+/// A websocket carrying OSC packets in binary frames and [`Command`]s in
+/// text frames.
+///
+/// # Example
+///
+/// ```no_run
+/// use rosc::oscquery::{WebSocket, WsMessage};
+///
+/// let mut ws = WebSocket::connect("127.0.0.1:8080").unwrap();
+/// ws.listen("/mixer/1/gain").unwrap();
+/// while let Some(WsMessage::Packet(packet)) = ws.recv().unwrap() {
+///     println!("{:?}", packet);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct WebSocket<S> {
+    stream: S,
+    // Frames sent by clients must be masked
+    mask: bool,
+}
+
+impl WebSocket<TcpStream> {
+    /// Connects to the OSCQuery server at `addr` and performs the websocket
+    /// handshake.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, OscQueryError> {
+        let stream = TcpStream::connect(addr)?;
+        let key = BASE64.encode(random_bytes::<16>());
+        write!(
+            &stream,
+            "GET / HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            stream.peer_addr()?,
+            key
+        )?;
+
+        // Frames following the handshake must not end up in the buffer
+        let mut reader = BufReader::with_capacity(1, &stream);
+        let status =
+            read_line(&mut reader)?.ok_or(OscQueryError::BadResponse("missing status line"))?;
+        if status.split_whitespace().nth(1) != Some("101") {
+            return Err(OscQueryError::WebSocket("server refused the upgrade"));
+        }
+        let mut accepted = false;
+        loop {
+            let line =
+                read_line(&mut reader)?.ok_or(OscQueryError::BadResponse("truncated headers"))?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("sec-websocket-accept") {
+                    accepted = value.trim() == accept_key(&key);
+                }
+            }
+        }
+        if !accepted {
+            return Err(OscQueryError::WebSocket("invalid Sec-WebSocket-Accept"));
+        }
+        Ok(WebSocket::client(stream))
+    }
+}
+
+impl<S: Read + Write> WebSocket<S> {
+    /// Wraps the client side of a stream after a completed handshake.
+    pub fn client(stream: S) -> Self {
+        WebSocket { stream, mask: true }
+    }
+
+    /// Wraps the server side of a stream after a completed handshake.
+    pub fn server(stream: S) -> Self {
+        WebSocket {
+            stream,
+            mask: false,
+        }
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Sends an OSC packet as a binary frame.
+    pub fn send_packet(&mut self, packet: &OscPacket) -> Result<(), OscQueryError> {
+        let bytes = encoder::encode(packet)?;
+        self.write_frame(OP_BINARY, &bytes)?;
+        Ok(())
+    }
+
+    /// Sends a command as a text frame.
+    pub fn send_command(&mut self, command: &Command) -> Result<(), OscQueryError> {
+        self.write_frame(OP_TEXT, command.to_json().to_string().as_bytes())?;
+        Ok(())
+    }
+
+    /// Subscribes to the values of the method at `path`.
+    pub fn listen(&mut self, path: &str) -> Result<(), OscQueryError> {
+        self.send_command(&Command::Listen(path.to_string()))
+    }
+
+    /// Cancels the subscription to the values of the method at `path`.
+    pub fn ignore(&mut self, path: &str) -> Result<(), OscQueryError> {
+        self.send_command(&Command::Ignore(path.to_string()))
+    }
+
+    /// Receives the next message, answering pings on the way. Returns `None`
+    /// once the peer closed the websocket.
+    pub fn recv(&mut self) -> Result<Option<WsMessage>, OscQueryError> {
+        loop {
+            match read_message(&mut self.stream)? {
+                Incoming::Message(msg) => return Ok(Some(msg)),
+                Incoming::Ping(payload) => self.write_frame(OP_PONG, &payload)?,
+                Incoming::Close => {
+                    // The peer may close the connection without waiting
+                    let _ = self.write_frame(OP_CLOSE, &[]);
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    /// Starts the closing handshake.
+    pub fn close(&mut self) -> Result<(), OscQueryError> {
+        self.write_frame(OP_CLOSE, &[])?;
+        Ok(())
+    }
+
+    fn write_frame(&mut self, opcode: u8, payload: &[u8]) -> io::Result<()> {
+        let mask = if self.mask {
+            Some(random_bytes::<4>())
+        } else {
+            None
+        };
+        write_frame(&mut self.stream, opcode, payload, mask)
+    }
+}
+
+/// A websocket message or control frame, fragmented messages are already
+/// reassembled.
+pub(crate) enum Incoming {
+    Message(WsMessage),
+    Ping(Vec<u8>),
+    Close,
+}
+
+/// Reads frames until a complete message or a ping or close frame arrived.
+pub(crate) fn read_message<R: Read>(reader: &mut R) -> Result<Incoming, OscQueryError> {
+    let mut message: Option<(u8, Vec<u8>)> = None;
+    loop {
+        let mut header = [0u8; 2];
+        reader.read_exact(&mut header)?;
+        let fin = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0f;
+        if header[0] & 0x70 != 0 {
+            return Err(OscQueryError::WebSocket("reserved bits are set"));
+        }
+        let masked = header[1] & 0x80 != 0;
+        let length = match header[1] & 0x7f {
+            126 => {
+                let mut length = [0u8; 2];
+                reader.read_exact(&mut length)?;
+                u64::from(u16::from_be_bytes(length))
+            }
+            127 => {
+                let mut length = [0u8; 8];
+                reader.read_exact(&mut length)?;
+                u64::from_be_bytes(length)
+            }
+            length => u64::from(length),
+        };
+        let buffered = message.as_ref().map_or(0, |(_, payload)| payload.len());
+        if length > (MAX_MESSAGE - buffered) as u64 {
+            return Err(OscQueryError::WebSocket("message too large"));
+        }
+        let mut mask = [0u8; 4];
+        if masked {
+            reader.read_exact(&mut mask)?;
+        }
+        let mut payload = vec![0u8; length as usize];
+        reader.read_exact(&mut payload)?;
+        if masked {
+            payload
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, byte)| *byte ^= mask[i % 4]);
+        }
+
+        // Control frames may be interleaved with the fragments of a message
+        let (opcode, payload) = match opcode {
+            OP_CLOSE => return Ok(Incoming::Close),
+            OP_PING => return Ok(Incoming::Ping(payload)),
+            OP_PONG => continue,
+            OP_TEXT | OP_BINARY if message.is_none() => (opcode, payload),
+            OP_CONTINUATION => match message.take() {
+                Some((opcode, mut buffered)) => {
+                    buffered.extend_from_slice(&payload);
+                    (opcode, buffered)
+                }
+                None => return Err(OscQueryError::WebSocket("unexpected continuation frame")),
+            },
+            _ => return Err(OscQueryError::WebSocket("unexpected opcode")),
+        };
+        if !fin {
+            message = Some((opcode, payload));
+            continue;
+        }
+        return match opcode {
+            OP_BINARY => {
+                let (_, packet) = decoder::decode_udp(&payload)?;
+                Ok(Incoming::Message(WsMessage::Packet(packet)))
+            }
+            _ => {
+                let command = serde_json::from_slice(&payload)?;
+                Command::from_json(&command)
+                    .map(|command| Incoming::Message(WsMessage::Command(command)))
+                    .ok_or(OscQueryError::WebSocket("unknown command"))
+            }
+        };
+    }
+}
+
+/// Writes a single unfragmented frame, masked if `mask` is set.
+pub(crate) fn write_frame<W: Write>(
+    writer: &mut W,
+    opcode: u8,
+    payload: &[u8],
+    mask: Option<[u8; 4]>,
+) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 14);
+    frame.push(0x80 | opcode);
+    let mask_bit = if mask.is_some() { 0x80 } else { 0 };
+    match payload.len() {
+        length @ 0..=125 => frame.push(mask_bit | length as u8),
+        length @ 126..=0xffff => {
+            frame.push(mask_bit | 126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(mask_bit | 127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    match mask {
+        Some(mask) => {
+            frame.extend_from_slice(&mask);
+            frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        }
+        None => frame.extend_from_slice(payload),
+    }
+    writer.write_all(&frame)?;
+    writer.flush()
+}
+
+/// Returns the `Sec-WebSocket-Accept` value for the handshake `key`.
+pub(crate) fn accept_key(key: &str) -> String {
+    let mut sha1 = sha1_smol::Sha1::new();
+    sha1.update(key.as_bytes());
+    sha1.update(HANDSHAKE_GUID.as_bytes());
+    BASE64.encode(sha1.digest().bytes())
+}
+
+/// Returns bytes which are unpredictable enough for masking keys and
+/// handshake nonces.
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    for chunk in bytes.chunks_mut(8) {
+        let random = RandomState::new().build_hasher().finish().to_le_bytes();
+        chunk.copy_from_slice(&random[..chunk.len()]);
+    }
+    bytes
+}
@@ -0,0 +1,613 @@
+use crate::decoder;
+use crate::errors::OscError;
+use crate::io_util;
+use crate::types::{OscPacket, OscTime};
+
+use std::convert::TryFrom;
+use std::error;
+use std::fmt::{self, Display};
+use std::io::{self, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, UNIX_EPOCH};
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+const PCAPNG_SIMPLE_PACKET: u32 = 0x0000_0003;
+const PCAPNG_ENHANCED_PACKET: u32 = 0x0000_0006;
+const PCAPNG_OPTION_END: u16 = 0;
+const PCAPNG_OPTION_TSRESOL: u16 = 9;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LOOP: u32 = 108;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88a8;
+
+const IP_PROTOCOL_UDP: u8 = 17;
+
+/// Upper bound for a single block or record, protects against allocating
+/// huge buffers for corrupt length fields.
+const MAX_RECORD_SIZE: usize = 256 * 1024 * 1024;
+
+/// Errors returned while reading a capture file.
+#[derive(Debug)]
+pub enum CaptureError {
+    /// The underlying reader failed.
+    Io(io::Error),
+    /// The UDP payload of a captured packet is not a valid OSC packet.
+    Osc(OscError),
+    /// The file is not a valid pcap or pcapng file.
+    BadFormat(&'static str),
+    /// The capture uses a link-layer header type that can't be parsed.
+    UnsupportedLinkType(u32),
+}
+
+impl Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CaptureError::Io(err) => write!(f, "capture I/O error: {}", err),
+            CaptureError::Osc(err) => write!(f, "captured packet is invalid: {}", err),
+            CaptureError::BadFormat(msg) => write!(f, "bad capture file: {}", msg),
+            CaptureError::UnsupportedLinkType(link_type) => {
+                write!(f, "unsupported link-layer header type: {}", link_type)
+            }
+        }
+    }
+}
+
+impl error::Error for CaptureError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CaptureError::Io(err) => Some(err),
+            CaptureError::Osc(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CaptureError {
+    fn from(err: io::Error) -> Self {
+        CaptureError::Io(err)
+    }
+}
+
+/// An OSC packet extracted from a capture file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CapturedPacket {
+    /// Capture timestamp of the frame that carried the packet.
+    pub time: OscTime,
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub packet: OscPacket,
+}
+
+/// Reads OSC-over-UDP traffic from a pcap or pcapng capture file, e.g. one
+/// written by `tcpdump -w`.
+///
+/// `CaptureReader` is an iterator over the OSC packets found in the capture.
+/// Only UDP datagrams whose source or destination port was configured with
+/// [`ports`](CaptureReader::ports) are decoded, all other frames are skipped.
+/// If no ports are configured, every UDP datagram is decoded.
+///
+/// A payload that fails to decode is reported as [`CaptureError::Osc`], but
+/// iteration can continue with the next frame. IP fragments are skipped
+/// since reassembly is not supported.
+///
+/// # Example
+///
+/// ```no_run
+/// use rosc::pcap::CaptureReader;
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// let file = BufReader::new(File::open("venue.pcapng").unwrap());
+/// for captured in CaptureReader::new(file).unwrap().ports(&[9000]) {
+///     match captured {
+///         Ok(captured) => println!("{} -> {}: {}", captured.source, captured.destination, captured.packet),
+///         Err(err) => eprintln!("{}", err),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CaptureReader<R: Read> {
+    reader: R,
+    format: Format,
+    ports: Vec<u16>,
+    done: bool,
+}
+
+#[derive(Debug)]
+enum Format {
+    Pcap {
+        big_endian: bool,
+        nanos: bool,
+        link_type: u32,
+    },
+    PcapNg {
+        big_endian: bool,
+        interfaces: Vec<Interface>,
+    },
+}
+
+#[derive(Debug)]
+struct Interface {
+    link_type: u32,
+    // Number of timestamp units per second
+    ticks_per_second: u64,
+}
+
+/// A captured link-layer frame.
+struct Frame {
+    link_type: u32,
+    time: Duration,
+    data: Vec<u8>,
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// Creates a new reader and reads the file header to detect whether the
+    /// capture is a pcap or pcapng file.
+    pub fn new(mut reader: R) -> Result<Self, CaptureError> {
+        let mut magic = [0u8; 4];
+        read_exact(&mut reader, &mut magic)?;
+
+        let format = if u32::from_le_bytes(magic) == PCAPNG_SECTION_HEADER {
+            let big_endian = read_section_header(&mut reader)?;
+            Format::PcapNg {
+                big_endian,
+                interfaces: Vec::new(),
+            }
+        } else {
+            let (big_endian, nanos) = match (u32::from_be_bytes(magic), u32::from_le_bytes(magic)) {
+                (PCAP_MAGIC_MICROS, _) => (true, false),
+                (PCAP_MAGIC_NANOS, _) => (true, true),
+                (_, PCAP_MAGIC_MICROS) => (false, false),
+                (_, PCAP_MAGIC_NANOS) => (false, true),
+                _ => return Err(CaptureError::BadFormat("Not a pcap or pcapng file")),
+            };
+            let mut header = [0u8; 20];
+            read_exact(&mut reader, &mut header)?;
+            Format::Pcap {
+                big_endian,
+                nanos,
+                link_type: read_u32(&header[16..], big_endian),
+            }
+        };
+
+        Ok(CaptureReader {
+            reader,
+            format,
+            ports: Vec::new(),
+            done: false,
+        })
+    }
+
+    /// Only decode UDP datagrams sent from or to one of the given ports.
+    pub fn ports(mut self, ports: &[u16]) -> Self {
+        self.ports = ports.to_vec();
+        self
+    }
+
+    fn next_frame(&mut self) -> Result<Option<Frame>, CaptureError> {
+        match self.format {
+            Format::Pcap {
+                big_endian,
+                nanos,
+                link_type,
+            } => {
+                let mut header = [0u8; 16];
+                if !read_or_eof(&mut self.reader, &mut header)? {
+                    return Ok(None);
+                }
+                let seconds = read_u32(&header, big_endian);
+                let fraction = read_u32(&header[4..], big_endian);
+                let captured_len = read_u32(&header[8..], big_endian) as usize;
+                let data = read_vec(&mut self.reader, captured_len)?;
+                let time = if nanos {
+                    Duration::new(seconds.into(), fraction)
+                } else {
+                    Duration::new(seconds.into(), 0) + Duration::from_micros(fraction.into())
+                };
+                Ok(Some(Frame {
+                    link_type,
+                    time,
+                    data,
+                }))
+            }
+            Format::PcapNg { .. } => loop {
+                if let Some(frame) = self.next_pcapng_block()? {
+                    return Ok(Some(frame));
+                }
+                if self.done {
+                    return Ok(None);
+                }
+            },
+        }
+    }
+
+    /// Reads the next pcapng block, returning a frame if it is a packet block.
+    fn next_pcapng_block(&mut self) -> Result<Option<Frame>, CaptureError> {
+        let big_endian = match self.format {
+            Format::PcapNg { big_endian, .. } => big_endian,
+            Format::Pcap { .. } => unreachable!(),
+        };
+        let mut header = [0u8; 8];
+        if !read_or_eof(&mut self.reader, &mut header)? {
+            self.done = true;
+            return Ok(None);
+        }
+
+        let block_type = read_u32(&header, big_endian);
+        if block_type == PCAPNG_SECTION_HEADER {
+            // A new section may switch the byte order and always resets the interfaces
+            let length = [header[4], header[5], header[6], header[7]];
+            let big_endian = read_section_header_with_length(&mut self.reader, length)?;
+            self.format = Format::PcapNg {
+                big_endian,
+                interfaces: Vec::new(),
+            };
+            return Ok(None);
+        }
+
+        let total_len = read_u32(&header[4..], big_endian) as usize;
+        if total_len < 12 || total_len % 4 != 0 {
+            return Err(CaptureError::BadFormat("Invalid pcapng block length"));
+        }
+        let block = read_vec(&mut self.reader, total_len - 8)?;
+        // The block body is followed by a copy of the total length
+        let body = &block[..block.len() - 4];
+
+        let interfaces = match self.format {
+            Format::PcapNg {
+                ref mut interfaces, ..
+            } => interfaces,
+            Format::Pcap { .. } => unreachable!(),
+        };
+        match block_type {
+            PCAPNG_INTERFACE_DESCRIPTION => {
+                if body.len() < 8 {
+                    return Err(CaptureError::BadFormat("Interface description too short"));
+                }
+                let link_type = u32::from(read_u16(body, big_endian));
+                let ticks_per_second = read_tsresol(&body[8..], big_endian)?;
+                interfaces.push(Interface {
+                    link_type,
+                    ticks_per_second,
+                });
+                Ok(None)
+            }
+            PCAPNG_ENHANCED_PACKET => {
+                if body.len() < 20 {
+                    return Err(CaptureError::BadFormat("Enhanced packet block too short"));
+                }
+                let interface = interfaces
+                    .get(read_u32(body, big_endian) as usize)
+                    .ok_or(CaptureError::BadFormat("Packet for unknown interface"))?;
+                let ticks = (u64::from(read_u32(&body[4..], big_endian)) << 32)
+                    | u64::from(read_u32(&body[8..], big_endian));
+                let captured_len = read_u32(&body[12..], big_endian) as usize;
+                let data = body
+                    .get(20..20 + captured_len)
+                    .ok_or(CaptureError::BadFormat("Packet data exceeds block"))?;
+                let time = Duration::new(
+                    ticks / interface.ticks_per_second,
+                    (u128::from(ticks % interface.ticks_per_second) * 1_000_000_000
+                        / u128::from(interface.ticks_per_second)) as u32,
+                );
+                Ok(Some(Frame {
+                    link_type: interface.link_type,
+                    time,
+                    data: data.to_vec(),
+                }))
+            }
+            PCAPNG_SIMPLE_PACKET => {
+                if body.len() < 4 {
+                    return Err(CaptureError::BadFormat("Simple packet block too short"));
+                }
+                let interface = interfaces
+                    .first()
+                    .ok_or(CaptureError::BadFormat("Packet for unknown interface"))?;
+                let original_len = read_u32(body, big_endian) as usize;
+                let data = &body[4..];
+                // Simple packet blocks carry no timestamp
+                Ok(Some(Frame {
+                    link_type: interface.link_type,
+                    time: Duration::ZERO,
+                    data: data[..original_len.min(data.len())].to_vec(),
+                }))
+            }
+            // Statistics, name resolution, custom blocks, etc.
+            _ => Ok(None),
+        }
+    }
+
+    fn decode_frame(&self, frame: Frame) -> Option<Result<CapturedPacket, CaptureError>> {
+        let (ip, data) = match link_payload(frame.link_type, &frame.data) {
+            Ok(Some(payload)) => payload,
+            Ok(None) => return None,
+            Err(err) => return Some(Err(err)),
+        };
+        let (source_ip, destination_ip, udp) = match ip {
+            IpVersion::V4 => ipv4_udp(data)?,
+            IpVersion::V6 => ipv6_udp(data)?,
+        };
+        if udp.len() < 8 {
+            return None;
+        }
+        let source_port = u16::from_be_bytes([udp[0], udp[1]]);
+        let destination_port = u16::from_be_bytes([udp[2], udp[3]]);
+        if !self.ports.is_empty()
+            && !self.ports.contains(&source_port)
+            && !self.ports.contains(&destination_port)
+        {
+            return None;
+        }
+        let udp_len = usize::from(u16::from_be_bytes([udp[4], udp[5]]));
+        let payload = &udp[8..udp_len.clamp(8, udp.len())];
+
+        // Timestamps come straight from the file, so they may not even fit a `SystemTime`
+        let time = match UNIX_EPOCH
+            .checked_add(frame.time)
+            .and_then(|time| OscTime::try_from(time).ok())
+        {
+            Some(time) => time,
+            None => return Some(Err(CaptureError::BadFormat("Invalid capture timestamp"))),
+        };
+        Some(
+            decoder::decode_udp(payload)
+                .map(|(_, packet)| CapturedPacket {
+                    time,
+                    source: SocketAddr::new(source_ip, source_port),
+                    destination: SocketAddr::new(destination_ip, destination_port),
+                    packet,
+                })
+                .map_err(CaptureError::Osc),
+        )
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = Result<CapturedPacket, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let frame = match self.next_frame() {
+                Ok(Some(frame)) => frame,
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(err) => {
+                    // The position of the next frame is unknown after a format error
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            };
+            if let Some(result) = self.decode_frame(frame) {
+                return Some(result);
+            }
+        }
+        None
+    }
+}
+
+enum IpVersion {
+    V4,
+    V6,
+}
+
+/// Strips the link-layer header and returns the IP version and packet,
+/// or `None` if the frame does not contain an IP packet.
+fn link_payload(link_type: u32, data: &[u8]) -> Result<Option<(IpVersion, &[u8])>, CaptureError> {
+    let (ethertype, payload) = match link_type {
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            let mut ethertype = read_be_u16_at(data, offset);
+            // Skip 802.1Q and 802.1ad tags
+            while matches!(ethertype, Some(ETHERTYPE_VLAN) | Some(ETHERTYPE_QINQ)) {
+                offset += 4;
+                ethertype = read_be_u16_at(data, offset);
+            }
+            (ethertype, data.get(offset + 2..))
+        }
+        LINKTYPE_LINUX_SLL => (read_be_u16_at(data, 14), data.get(16..)),
+        LINKTYPE_LINUX_SLL2 => (read_be_u16_at(data, 0), data.get(20..)),
+        LINKTYPE_NULL | LINKTYPE_LOOP => {
+            // The address family is in the byte order of the capturing host
+            let family = data.get(..4).map(|b| {
+                let family = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+                if family > 0xffff {
+                    family.swap_bytes()
+                } else {
+                    family
+                }
+            });
+            let ethertype = match family {
+                Some(2) => Some(ETHERTYPE_IPV4),
+                // IPv6 on BSD, FreeBSD and Darwin respectively
+                Some(24) | Some(28) | Some(30) => Some(ETHERTYPE_IPV6),
+                _ => None,
+            };
+            (ethertype, data.get(4..))
+        }
+        LINKTYPE_RAW => match data.first().map(|b| b >> 4) {
+            Some(4) => (Some(ETHERTYPE_IPV4), Some(data)),
+            Some(6) => (Some(ETHERTYPE_IPV6), Some(data)),
+            _ => (None, None),
+        },
+        LINKTYPE_IPV4 => (Some(ETHERTYPE_IPV4), Some(data)),
+        LINKTYPE_IPV6 => (Some(ETHERTYPE_IPV6), Some(data)),
+        _ => return Err(CaptureError::UnsupportedLinkType(link_type)),
+    };
+    Ok(match (ethertype, payload) {
+        (Some(ETHERTYPE_IPV4), Some(payload)) => Some((IpVersion::V4, payload)),
+        (Some(ETHERTYPE_IPV6), Some(payload)) => Some((IpVersion::V6, payload)),
+        _ => None,
+    })
+}
+
+fn ipv4_udp(data: &[u8]) -> Option<(IpAddr, IpAddr, &[u8])> {
+    if data.len() < 20 || data[0] >> 4 != 4 || data[9] != IP_PROTOCOL_UDP {
+        return None;
+    }
+    // Skip fragments, the "more fragments" flag or a fragment offset is set
+    if u16::from_be_bytes([data[6], data[7]]) & 0x3fff != 0 {
+        return None;
+    }
+    let header_len = usize::from(data[0] & 0x0f) * 4;
+    let total_len = usize::from(u16::from_be_bytes([data[2], data[3]]));
+    let source = Ipv4Addr::new(data[12], data[13], data[14], data[15]);
+    let destination = Ipv4Addr::new(data[16], data[17], data[18], data[19]);
+    let end = if total_len >= header_len {
+        total_len.min(data.len())
+    } else {
+        data.len()
+    };
+    Some((
+        IpAddr::V4(source),
+        IpAddr::V4(destination),
+        data.get(header_len..end)?,
+    ))
+}
+
+fn ipv6_udp(data: &[u8]) -> Option<(IpAddr, IpAddr, &[u8])> {
+    if data.len() < 40 || data[0] >> 4 != 6 {
+        return None;
+    }
+    let mut octets = [0u8; 16];
+    octets.copy_from_slice(&data[8..24]);
+    let source = Ipv6Addr::from(octets);
+    octets.copy_from_slice(&data[24..40]);
+    let destination = Ipv6Addr::from(octets);
+
+    let payload_len = usize::from(u16::from_be_bytes([data[4], data[5]]));
+    let mut next_header = data[6];
+    let mut payload = &data[40..(40 + payload_len).min(data.len())];
+    loop {
+        match next_header {
+            IP_PROTOCOL_UDP => return Some((IpAddr::V6(source), IpAddr::V6(destination), payload)),
+            // Hop-by-hop, routing and destination options extension headers
+            0 | 43 | 60 => {
+                let len = (usize::from(*payload.get(1)?) + 1) * 8;
+                next_header = *payload.first()?;
+                payload = payload.get(len..)?;
+            }
+            // Fragments and everything else
+            _ => return None,
+        }
+    }
+}
+
+/// Reads the remainder of a section header block after its block type.
+fn read_section_header<R: Read>(reader: &mut R) -> Result<bool, CaptureError> {
+    let mut length = [0u8; 4];
+    read_exact(reader, &mut length)?;
+    read_section_header_with_length(reader, length)
+}
+
+fn read_section_header_with_length<R: Read>(
+    reader: &mut R,
+    length: [u8; 4],
+) -> Result<bool, CaptureError> {
+    let mut magic = [0u8; 4];
+    read_exact(reader, &mut magic)?;
+    let big_endian = match u32::from_be_bytes(magic) {
+        PCAPNG_BYTE_ORDER_MAGIC => true,
+        m if m.swap_bytes() == PCAPNG_BYTE_ORDER_MAGIC => false,
+        _ => return Err(CaptureError::BadFormat("Invalid pcapng byte-order magic")),
+    };
+    let total_len = read_u32(&length, big_endian) as usize;
+    if total_len < 28 || total_len % 4 != 0 {
+        return Err(CaptureError::BadFormat("Invalid pcapng block length"));
+    }
+    // Skip the version, section length, options and trailing block length
+    read_vec(reader, total_len - 12)?;
+    Ok(big_endian)
+}
+
+/// Returns the number of timestamp units per second from the options of an
+/// interface description block.
+fn read_tsresol(mut options: &[u8], big_endian: bool) -> Result<u64, CaptureError> {
+    while options.len() >= 4 {
+        let code = read_u16(options, big_endian);
+        let len = usize::from(read_u16(&options[2..], big_endian));
+        let value = options
+            .get(4..4 + len)
+            .ok_or(CaptureError::BadFormat("Truncated interface option"))?;
+        match code {
+            PCAPNG_OPTION_END => break,
+            PCAPNG_OPTION_TSRESOL if len == 1 => {
+                let resolution = value[0];
+                let exponent = u32::from(resolution & 0x7f);
+                return if resolution & 0x80 == 0 {
+                    10u64.checked_pow(exponent)
+                } else {
+                    2u64.checked_pow(exponent)
+                }
+                .ok_or(CaptureError::BadFormat("Unsupported timestamp resolution"));
+            }
+            _ => {}
+        }
+        options = options.get(4 + (len + 3) / 4 * 4..).unwrap_or(&[]);
+    }
+    Ok(1_000_000)
+}
+
+fn read_u16(buf: &[u8], big_endian: bool) -> u16 {
+    let bytes = [buf[0], buf[1]];
+    if big_endian {
+        u16::from_be_bytes(bytes)
+    } else {
+        u16::from_le_bytes(bytes)
+    }
+}
+
+fn read_u32(buf: &[u8], big_endian: bool) -> u32 {
+    let bytes = [buf[0], buf[1], buf[2], buf[3]];
+    if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    }
+}
+
+fn read_be_u16_at(buf: &[u8], offset: usize) -> Option<u16> {
+    buf.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), CaptureError> {
+    reader.read_exact(buf).map_err(read_error)
+}
+
+/// Fills `buf` or returns `false` if the reader is at the end of the file.
+fn read_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<bool, CaptureError> {
+    io_util::read_or_eof(reader, buf).map_err(read_error)
+}
+
+fn read_error(err: io::Error) -> CaptureError {
+    match err.kind() {
+        io::ErrorKind::UnexpectedEof => CaptureError::BadFormat("Truncated capture file"),
+        _ => CaptureError::Io(err),
+    }
+}
+
+fn read_vec<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>, CaptureError> {
+    if len > MAX_RECORD_SIZE {
+        return Err(CaptureError::BadFormat("Record exceeds maximum size"));
+    }
+    let mut buf = vec![0u8; len];
+    read_exact(reader, &mut buf)?;
+    Ok(buf)
+}
@@ -0,0 +1,457 @@
+use crate::decoder;
+use crate::encoder;
+use crate::errors::OscError;
+use crate::io_util;
+use crate::types::{OscPacket, OscTime};
+
+use std::fmt::{self, Display};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant, SystemTime};
+use std::{error, thread};
+
+/// Magic bytes at the start of every recording.
+const MAGIC: &[u8; 8] = b"ROSCREC\0";
+/// Version of the recording format written by [`Recorder`].
+const VERSION: u16 = 1;
+
+const NO_ADDRESS: u8 = 0;
+const IPV4_ADDRESS: u8 = 4;
+const IPV6_ADDRESS: u8 = 6;
+
+/// Errors returned while writing or reading a recording.
+#[derive(Debug)]
+pub enum RecordingError {
+    /// The underlying reader or writer failed.
+    Io(io::Error),
+    /// A recorded packet could not be decoded.
+    Osc(OscError),
+    /// The data does not start with a recording header.
+    BadHeader,
+    /// The recording was written with a format version this crate can't read.
+    UnsupportedVersion(u16),
+    /// A record is malformed, e.g. it has an unknown address family.
+    BadRecord(&'static str),
+    /// A playback time is outside of the range of OSC time tags.
+    TimeOutOfRange,
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordingError::Io(err) => write!(f, "recording I/O error: {}", err),
+            RecordingError::Osc(err) => write!(f, "recorded packet is invalid: {}", err),
+            RecordingError::BadHeader => write!(f, "missing or invalid recording header"),
+            RecordingError::UnsupportedVersion(version) => {
+                write!(f, "unsupported recording version: {}", version)
+            }
+            RecordingError::BadRecord(msg) => write!(f, "bad record: {}", msg),
+            RecordingError::TimeOutOfRange => write!(f, "playback time is out of range"),
+        }
+    }
+}
+
+impl error::Error for RecordingError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            RecordingError::Io(err) => Some(err),
+            RecordingError::Osc(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RecordingError {
+    fn from(err: io::Error) -> Self {
+        RecordingError::Io(err)
+    }
+}
+
+impl From<OscError> for RecordingError {
+    fn from(err: OscError) -> Self {
+        RecordingError::Osc(err)
+    }
+}
+
+/// A single packet of a recording together with the time it was received
+/// and the address it was received from, if known.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub time: OscTime,
+    pub source: Option<SocketAddr>,
+    pub packet: OscPacket,
+}
+
+/// Writes OSC packets to a compact binary log.
+///
+/// A recording starts with a small header followed by one record per packet.
+/// Each record holds the receive time, the source address and the packet as
+/// produced by [`encoder::encode`], prefixed with its length.
+///
+/// # Example
+///
+/// ```
+/// use rosc::recording::{Reader, Recorder};
+/// use rosc::{OscMessage, OscPacket, OscTime};
+///
+/// let packet = OscPacket::Message(OscMessage::from("/tempo"));
+///
+/// let mut recorder = Recorder::new(Vec::new()).unwrap();
+/// recorder.record(OscTime::from((1, 0)), None, &packet).unwrap();
+/// let log = recorder.into_inner();
+///
+/// let records = Reader::new(&log[..]).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(records[0].packet, packet);
+/// ```
+#[derive(Debug)]
+pub struct Recorder<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Creates a new recorder and writes the recording header to `writer`.
+    ///
+    /// Records are written in small pieces, so `writer` should be buffered
+    /// if write calls have a large overhead, e.g. when writing to a file.
+    pub fn new(mut writer: W) -> Result<Self, RecordingError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_be_bytes())?;
+        writer.write_all(&[0u8; 2])?;
+        Ok(Recorder { writer })
+    }
+
+    /// Encodes `packet` and appends it to the recording.
+    pub fn record(
+        &mut self,
+        time: OscTime,
+        source: Option<SocketAddr>,
+        packet: &OscPacket,
+    ) -> Result<(), RecordingError> {
+        let bytes = encoder::encode(packet)?;
+        self.record_raw(time, source, &bytes)
+    }
+
+    /// Appends an already encoded packet to the recording, e.g. the bytes
+    /// returned by `UdpSocket::recv_from`.
+    ///
+    /// The bytes are checked to be a valid OSC packet so that the recording
+    /// can always be read back.
+    pub fn record_raw(
+        &mut self,
+        time: OscTime,
+        source: Option<SocketAddr>,
+        bytes: &[u8],
+    ) -> Result<(), RecordingError> {
+        decoder::decode_udp(bytes)?;
+
+        self.writer.write_all(&time.seconds.to_be_bytes())?;
+        self.writer.write_all(&time.fractional.to_be_bytes())?;
+        match source {
+            None => self.writer.write_all(&[NO_ADDRESS])?,
+            Some(SocketAddr::V4(addr)) => {
+                self.writer.write_all(&[IPV4_ADDRESS])?;
+                self.writer.write_all(&addr.ip().octets())?;
+                self.writer.write_all(&addr.port().to_be_bytes())?;
+            }
+            Some(SocketAddr::V6(addr)) => {
+                self.writer.write_all(&[IPV6_ADDRESS])?;
+                self.writer.write_all(&addr.ip().octets())?;
+                self.writer.write_all(&addr.port().to_be_bytes())?;
+            }
+        }
+        let length = u32::try_from(bytes.len())
+            .map_err(|_| RecordingError::BadRecord("Packet is too large to be recorded"))?;
+        self.writer.write_all(&length.to_be_bytes())?;
+        self.writer.write_all(bytes)?;
+        Ok(())
+    }
+
+    /// Appends `packet` to the recording using the current system time as
+    /// its receive time.
+    pub fn record_now(
+        &mut self,
+        source: Option<SocketAddr>,
+        packet: &OscPacket,
+    ) -> Result<(), RecordingError> {
+        let time = OscTime::try_from(SystemTime::now())
+            .map_err(|_| RecordingError::BadRecord("System time can't be stored as OSC time"))?;
+        self.record(time, source, packet)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> Result<(), RecordingError> {
+        self.writer.flush().map_err(RecordingError::from)
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads the records of a recording written by [`Recorder`].
+///
+/// `Reader` is an iterator over the contained [`Record`]s, use
+/// [`replay`](Reader::replay) to play them back with their original timing.
+#[derive(Debug)]
+pub struct Reader<R: Read> {
+    reader: R,
+    done: bool,
+}
+
+impl<R: Read> Reader<R> {
+    /// Creates a new reader and checks the recording header.
+    pub fn new(mut reader: R) -> Result<Self, RecordingError> {
+        let mut header = [0u8; 12];
+        reader
+            .read_exact(&mut header)
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::UnexpectedEof => RecordingError::BadHeader,
+                _ => RecordingError::Io(err),
+            })?;
+        if &header[..8] != MAGIC {
+            return Err(RecordingError::BadHeader);
+        }
+        let version = u16::from_be_bytes([header[8], header[9]]);
+        if version != VERSION {
+            return Err(RecordingError::UnsupportedVersion(version));
+        }
+        Ok(Reader {
+            reader,
+            done: false,
+        })
+    }
+
+    /// Turns this reader into a [`Replay`] iterator.
+    pub fn replay(self) -> Replay<R> {
+        Replay::new(self)
+    }
+
+    fn read_record(&mut self) -> Result<Option<Record>, RecordingError> {
+        let mut time = [0u8; 8];
+        // A recording may end after any complete record
+        if !io_util::read_or_eof(&mut self.reader, &mut time).map_err(read_error)? {
+            return Ok(None);
+        }
+        let time = OscTime {
+            seconds: u32::from_be_bytes([time[0], time[1], time[2], time[3]]),
+            fractional: u32::from_be_bytes([time[4], time[5], time[6], time[7]]),
+        };
+        let mut family = [0u8; 1];
+        self.read_exact(&mut family)?;
+        let source = match family[0] {
+            NO_ADDRESS => None,
+            IPV4_ADDRESS => {
+                let mut buf = [0u8; 6];
+                self.read_exact(&mut buf)?;
+                let ip = Ipv4Addr::new(buf[0], buf[1], buf[2], buf[3]);
+                Some(SocketAddr::new(
+                    IpAddr::V4(ip),
+                    u16::from_be_bytes([buf[4], buf[5]]),
+                ))
+            }
+            IPV6_ADDRESS => {
+                let mut buf = [0u8; 18];
+                self.read_exact(&mut buf)?;
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(&buf[..16]);
+                Some(SocketAddr::new(
+                    IpAddr::V6(Ipv6Addr::from(octets)),
+                    u16::from_be_bytes([buf[16], buf[17]]),
+                ))
+            }
+            _ => return Err(RecordingError::BadRecord("Unknown address family")),
+        };
+        let mut length = [0u8; 4];
+        self.read_exact(&mut length)?;
+        let length = u64::from(u32::from_be_bytes(length));
+        // Memory only grows with the data actually present, whatever the length says
+        let mut bytes = Vec::new();
+        (&mut self.reader).take(length).read_to_end(&mut bytes)?;
+        if (bytes.len() as u64) < length {
+            return Err(RecordingError::BadRecord("Truncated record"));
+        }
+        let (_, packet) = decoder::decode_udp(&bytes)?;
+
+        Ok(Some(Record {
+            time,
+            source,
+            packet,
+        }))
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), RecordingError> {
+        self.reader.read_exact(buf).map_err(read_error)
+    }
+}
+
+fn read_error(err: io::Error) -> RecordingError {
+    match err.kind() {
+        io::ErrorKind::UnexpectedEof => RecordingError::BadRecord("Truncated record"),
+        _ => RecordingError::Io(err),
+    }
+}
+
+impl<R: Read> Iterator for Reader<R> {
+    type Item = Result<Record, RecordingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.read_record().transpose();
+        // Stop at the end of the recording or at the first error, since the
+        // position of the next record is unknown after a failed read.
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
+
+/// Plays back a recording, yielding every packet together with its
+/// playback time.
+///
+/// By default the playback times are the original receive times. The time
+/// line can be moved with [`starting_at`](Replay::starting_at) and
+/// [`offset`](Replay::offset), and stretched or compressed with
+/// [`speed`](Replay::speed). With [`paced`](Replay::paced) the iterator
+/// sleeps between packets so that they are yielded with the (scaled) timing
+/// at which they were recorded.
+///
+/// Playback times beyond the range of OSC time tags are reported as
+/// [`RecordingError::TimeOutOfRange`] instead of wrapping around.
+///
+/// # Example
+///
+/// ```
+/// use rosc::recording::{Reader, Recorder};
+/// use rosc::{OscMessage, OscPacket, OscTime};
+///
+/// let packet = OscPacket::Message(OscMessage::from("/tempo"));
+/// let mut recorder = Recorder::new(Vec::new()).unwrap();
+/// recorder.record(OscTime::from((10, 0)), None, &packet).unwrap();
+/// recorder.record(OscTime::from((12, 0)), None, &packet).unwrap();
+/// let log = recorder.into_inner();
+///
+/// let times = Reader::new(&log[..])
+///     .unwrap()
+///     .replay()
+///     .starting_at(OscTime::from((100, 0)))
+///     .speed(2.0)
+///     .map(|item| item.unwrap().0)
+///     .collect::<Vec<_>>();
+/// assert_eq!(times, vec![OscTime::from((100, 0)), OscTime::from((101, 0))]);
+/// ```
+#[derive(Debug)]
+pub struct Replay<R: Read> {
+    reader: Reader<R>,
+    speed: f64,
+    offset: Duration,
+    start: Option<OscTime>,
+    paced: bool,
+    // Receive time of the first record and the instant it was played back
+    origin: Option<(OscTime, Instant)>,
+}
+
+impl<R: Read> Replay<R> {
+    fn new(reader: Reader<R>) -> Self {
+        Replay {
+            reader,
+            speed: 1.0,
+            offset: Duration::ZERO,
+            start: None,
+            paced: false,
+            origin: None,
+        }
+    }
+
+    /// Sets the playback speed, e.g. `2.0` plays the recording twice as fast.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is not a positive finite number.
+    pub fn speed(mut self, speed: f64) -> Self {
+        assert!(
+            speed.is_finite() && speed > 0.0,
+            "playback speed must be positive"
+        );
+        self.speed = speed;
+        self
+    }
+
+    /// Moves every playback time forward by `offset`.
+    pub fn offset(mut self, offset: Duration) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Moves the playback time line so that the first packet is played at `time`.
+    pub fn starting_at(mut self, time: OscTime) -> Self {
+        self.start = Some(time);
+        self
+    }
+
+    /// Sleeps before yielding each packet so that the wall clock time between
+    /// packets matches their (scaled) recorded timing.
+    pub fn paced(mut self, paced: bool) -> Self {
+        self.paced = paced;
+        self
+    }
+}
+
+impl<R: Read> Iterator for Replay<R> {
+    type Item = Result<(OscTime, OscPacket), RecordingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.reader.next()? {
+            Ok(record) => record,
+            Err(err) => return Some(Err(err)),
+        };
+        let (first, started) = *self
+            .origin
+            .get_or_insert_with(|| (record.time, Instant::now()));
+
+        let elapsed = ticks(record.time).saturating_sub(ticks(first));
+        // Durations too long to represent are out of range anyway
+        let scaled = Duration::try_from_secs_f64(elapsed as f64 / TICKS_PER_SECOND / self.speed)
+            .unwrap_or(Duration::MAX);
+        let start = self.start.unwrap_or(first);
+        let time = match scaled
+            .checked_add(self.offset)
+            .and_then(duration_ticks)
+            .and_then(|ticks_after_start| ticks(start).checked_add(ticks_after_start))
+        {
+            Some(ticks) => from_ticks(ticks),
+            None => return Some(Err(RecordingError::TimeOutOfRange)),
+        };
+
+        if self.paced {
+            if let Some(wait) = scaled.checked_sub(started.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+        Some(Ok((time, record.packet)))
+    }
+}
+
+const TICKS_PER_SECOND: f64 = 4_294_967_296.0;
+
+/// Returns the OSC time as a 32.32 fixed point number.
+fn ticks(time: OscTime) -> u64 {
+    (u64::from(time.seconds) << 32) | u64::from(time.fractional)
+}
+
+fn from_ticks(ticks: u64) -> OscTime {
+    OscTime {
+        seconds: (ticks >> 32) as u32,
+        fractional: ticks as u32,
+    }
+}
+
+/// Returns the duration as a 32.32 fixed point number, or `None` if it's too
+/// long to be represented.
+fn duration_ticks(duration: Duration) -> Option<u64> {
+    let fractional = ((u128::from(duration.subsec_nanos()) << 32) + 500_000_000) / 1_000_000_000;
+    u64::try_from((u128::from(duration.as_secs()) << 32) + fractional).ok()
+}
@@ -0,0 +1,273 @@
+use crate::address::{Matcher, OscAddress};
+use crate::decoder::{self, DecodeOptions};
+use crate::encoder;
+use crate::errors::OscError;
+use crate::types::{OscBundle, OscMessage, OscPacket, OscType};
+
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+
+/// The argument types a [`Rule`] can convert arguments to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgType {
+    Int,
+    Long,
+    Float,
+    Double,
+    Bool,
+    String,
+}
+
+#[derive(Clone, Debug)]
+struct Coercion {
+    // `None` converts every argument
+    index: Option<usize>,
+    to: ArgType,
+}
+
+/// A routing rule of a [`Relay`].
+///
+/// A rule forwards every message whose address matches its address pattern
+/// to all of its destinations, after optionally rewriting the address prefix
+/// and converting arguments.
+///
+/// # Example
+///
+/// ```
+/// use rosc::relay::{ArgType, Rule};
+///
+/// let rule = Rule::new("/deck/a/*")
+///     .unwrap()
+///     .rewrite_prefix("/deck/a", "/mixer/ch/1")
+///     .coerce_args(ArgType::Float)
+///     .forward_to("127.0.0.1:9001".parse().unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Rule {
+    matcher: Matcher,
+    destinations: Vec<SocketAddr>,
+    rewrite: Option<(String, String)>,
+    coercions: Vec<Coercion>,
+}
+
+impl Rule {
+    /// Creates a rule for messages matching the given address pattern.
+    /// An error will be returned if the address pattern is invalid.
+    pub fn new(pattern: &str) -> Result<Self, OscError> {
+        Ok(Rule {
+            matcher: Matcher::new(pattern)?,
+            destinations: Vec::new(),
+            rewrite: None,
+            coercions: Vec::new(),
+        })
+    }
+
+    /// Adds a destination that matching messages are forwarded to.
+    pub fn forward_to(mut self, destination: SocketAddr) -> Self {
+        self.destinations.push(destination);
+        self
+    }
+
+    /// Replaces the address prefix `from` with `to`, e.g. rewriting
+    /// `/deck/a/play` to `/mixer/ch/1/play` for the prefixes `/deck/a` and
+    /// `/mixer/ch/1`.
+    ///
+    /// The prefix only matches whole address parts, so `/deck/a` does not
+    /// apply to `/deck/ab`.
+    pub fn rewrite_prefix(mut self, from: &str, to: &str) -> Self {
+        self.rewrite = Some((
+            from.trim_end_matches('/').to_string(),
+            to.trim_end_matches('/').to_string(),
+        ));
+        self
+    }
+
+    /// Converts the argument at `index` to the given type.
+    pub fn coerce_arg(mut self, index: usize, to: ArgType) -> Self {
+        self.coercions.push(Coercion {
+            index: Some(index),
+            to,
+        });
+        self
+    }
+
+    /// Converts all arguments to the given type.
+    pub fn coerce_args(mut self, to: ArgType) -> Self {
+        self.coercions.push(Coercion { index: None, to });
+        self
+    }
+
+    fn matches(&self, msg: &OscMessage) -> bool {
+        // Messages sent to an address pattern can't be matched against a rule
+        OscAddress::new(msg.addr.clone())
+            .map(|addr| self.matcher.match_address(&addr))
+            .unwrap_or(false)
+    }
+
+    fn apply(&self, msg: &OscMessage) -> OscMessage {
+        let mut msg = msg.clone();
+        if let Some((from, to)) = &self.rewrite {
+            if let Some(rest) = msg.addr.strip_prefix(from.as_str()) {
+                if rest.is_empty() || rest.starts_with('/') {
+                    msg.addr = format!("{}{}", to, rest);
+                }
+            }
+        }
+        for coercion in &self.coercions {
+            match coercion.index {
+                Some(index) => {
+                    if let Some(arg) = msg.args.get_mut(index) {
+                        coerce(arg, coercion.to);
+                    }
+                }
+                None => msg.args.iter_mut().for_each(|arg| coerce(arg, coercion.to)),
+            }
+        }
+        msg
+    }
+}
+
+/// Converts `arg` in place, arguments that can't be converted are left unchanged.
+fn coerce(arg: &mut OscType, to: ArgType) {
+    let converted = match (to, arg.as_f64()) {
+        (ArgType::Int, Some(v)) => OscType::Int(v as i32),
+        (ArgType::Long, Some(v)) => OscType::Long(v as i64),
+        (ArgType::Float, Some(v)) => OscType::Float(v as f32),
+        (ArgType::Double, Some(v)) => OscType::Double(v),
+        (ArgType::Bool, Some(v)) => OscType::Bool(v != 0.0),
+        (ArgType::String, _) => match arg {
+            OscType::Int(v) => OscType::String(v.to_string()),
+            OscType::Long(v) => OscType::String(v.to_string()),
+            OscType::Float(v) => OscType::String(v.to_string()),
+            OscType::Double(v) => OscType::String(v.to_string()),
+            OscType::Bool(v) => OscType::String(v.to_string()),
+            OscType::Char(v) => OscType::String(v.to_string()),
+            _ => return,
+        },
+        _ => return,
+    };
+    *arg = converted;
+}
+
+/// Routes OSC packets to other hosts based on address patterns.
+///
+/// Every message is checked against all rules and forwarded by each
+/// matching rule, so a message can be fanned out to several destinations.
+/// Messages that match no rule are dropped. Bundles are forwarded as
+/// bundles with their original time tag, containing only the elements
+/// routed to the respective destination.
+///
+/// # Example
+///
+/// ```
+/// use rosc::relay::{Relay, Rule};
+/// use rosc::{OscMessage, OscPacket};
+///
+/// let mixer = "127.0.0.1:9001".parse().unwrap();
+/// let relay = Relay::new().rule(
+///     Rule::new("/deck/a/*")
+///         .unwrap()
+///         .rewrite_prefix("/deck/a", "/mixer/ch/1")
+///         .forward_to(mixer),
+/// );
+///
+/// let routed = relay.route(&OscPacket::Message(OscMessage::from("/deck/a/gain")));
+/// assert_eq!(
+///     routed,
+///     vec![(mixer, OscPacket::Message(OscMessage::from("/mixer/ch/1/gain")))]
+/// );
+/// assert!(relay.route(&OscPacket::Message(OscMessage::from("/deck/b/gain"))).is_empty());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Relay {
+    rules: Vec<Rule>,
+}
+
+impl Relay {
+    /// Creates a relay without any rules, which drops all packets.
+    pub fn new() -> Self {
+        Relay::default()
+    }
+
+    /// Adds a routing rule.
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Returns the packets to forward for `packet` together with their
+    /// destinations, in the order in which they should be sent.
+    pub fn route(&self, packet: &OscPacket) -> Vec<(SocketAddr, OscPacket)> {
+        match packet {
+            OscPacket::Message(msg) => self
+                .rules
+                .iter()
+                .filter(|rule| rule.matches(msg))
+                .flat_map(|rule| {
+                    let msg = rule.apply(msg);
+                    rule.destinations
+                        .iter()
+                        .map(move |destination| (*destination, OscPacket::Message(msg.clone())))
+                })
+                .collect(),
+            OscPacket::Bundle(bundle) => {
+                let mut routed: Vec<(SocketAddr, OscPacket)> = Vec::new();
+                for (destination, packet) in bundle.content.iter().flat_map(|p| self.route(p)) {
+                    match routed.iter_mut().find(|(d, _)| *d == destination) {
+                        Some((_, OscPacket::Bundle(b))) => b.content.push(packet),
+                        _ => routed.push((
+                            destination,
+                            OscPacket::Bundle(OscBundle {
+                                timetag: bundle.timetag,
+                                content: vec![packet],
+                            }),
+                        )),
+                    }
+                }
+                routed
+            }
+        }
+    }
+
+    /// Receives packets on `socket` and forwards them according to the rules
+    /// until receiving fails with an error which isn't transient.
+    ///
+    /// Datagrams which are not valid OSC packets are dropped. Arguments with
+    /// unknown type tags are forwarded unchanged, see
+    /// [`DecodeOptions::passthrough_unknown`]. A packet which can't be
+    /// encoded or sent is only dropped for its destination, the other
+    /// destinations still receive it.
+    pub fn serve(&self, socket: &UdpSocket) -> io::Result<()> {
+        let options = DecodeOptions::new().passthrough_unknown(true);
+        let mut buf = vec![0u8; 65_536];
+        loop {
+            let size = match socket.recv(&mut buf) {
+                Ok(size) => size,
+                Err(err) if is_transient(&err) => continue,
+                Err(err) => return Err(err),
+            };
+            let packet = match decoder::decode_udp_with(&buf[..size], &options) {
+                Ok((_, packet)) => packet,
+                Err(_) => continue,
+            };
+            for (destination, packet) in self.route(&packet) {
+                if let Ok(bytes) = encoder::encode(&packet) {
+                    let _ = socket.send_to(&bytes, destination);
+                }
+            }
+        }
+    }
+}
+
+/// Whether a receive error leaves the socket usable, e.g. an ICMP error
+/// reported for a datagram previously sent to an unreachable destination.
+fn is_transient(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+    )
+}
@@ -0,0 +1,225 @@
+use crate::alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use crate::decoder::TYPE_TAGS;
+use crate::errors::OscError;
+use crate::types::{OscMessage, OscType};
+use core::{fmt, str::FromStr};
+
+/// The type tags of an OSC method's arguments, e.g. `"ifs"` or `"i[ff]"`.
+///
+/// A signature is parsed using the same type tag grammar as the decoder.
+/// Messages can be checked against it, a mismatch reports the index of the
+/// first offending argument together with the expected and found type tags.
+/// Since `T` and `F` only differ in their value, both accept either boolean.
+///
+/// # Example
+///
+/// ```
+/// use rosc::{OscArray, OscMessage, OscType, TypeSignature};
+///
+/// let signature = TypeSignature::new("i[ff]").unwrap();
+/// let mut msg = OscMessage {
+///     addr: "/pos".to_string(),
+///     args: vec![
+///         OscType::Int(1),
+///         OscType::Array(OscArray::from_iter([0.5f32, 0.25])),
+///     ],
+/// };
+/// assert!(signature.check(&msg).is_ok());
+///
+/// msg.args[1] = OscType::Float(0.5);
+/// let mismatch = signature.check(&msg).unwrap_err();
+/// assert_eq!(mismatch.index, 1);
+/// assert_eq!(mismatch.expected.as_deref(), Some("[ff]"));
+/// assert_eq!(mismatch.found.as_deref(), Some("f"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TypeSignature {
+    // The normalized type tag string, including the leading `,`
+    tags: String,
+    args: Vec<Tag>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Tag {
+    Single(char),
+    Array(Vec<Tag>),
+}
+
+impl Tag {
+    fn matches(&self, arg: &OscType) -> bool {
+        match (self, arg) {
+            (Tag::Array(tags), OscType::Array(array)) => {
+                tags.len() == array.content.len()
+                    && tags.iter().zip(&array.content).all(|(t, a)| t.matches(a))
+            }
+            (Tag::Array(_), _) => false,
+            (Tag::Single('T' | 'F'), OscType::Bool(_)) => true,
+            (Tag::Single(tag), arg) => {
+                let mut found = String::new();
+                arg.push_type_tag(&mut found);
+                found.len() == 1 && found.starts_with(*tag)
+            }
+        }
+    }
+
+    fn push_to(&self, tags: &mut String) {
+        match self {
+            Tag::Single(tag) => tags.push(*tag),
+            Tag::Array(content) => {
+                tags.push('[');
+                content.iter().for_each(|tag| tag.push_to(tags));
+                tags.push(']');
+            }
+        }
+    }
+
+    fn to_tags(&self) -> String {
+        let mut tags = String::new();
+        self.push_to(&mut tags);
+        tags
+    }
+}
+
+impl TypeSignature {
+    /// Parses a type tag string, the leading `,` is optional.
+    ///
+    /// An error is returned for unknown type tags and unbalanced array
+    /// brackets.
+    pub fn new(type_tags: &str) -> Result<Self, OscError> {
+        let mut args = Vec::new();
+        let mut stack: Vec<Vec<Tag>> = Vec::new();
+        for tag in type_tags.strip_prefix(',').unwrap_or(type_tags).chars() {
+            if tag == '[' {
+                stack.push(args);
+                args = Vec::new();
+            } else if tag == ']' {
+                let array = Tag::Array(args);
+                args = stack
+                    .pop()
+                    .ok_or(OscError::BadMessage("Encountered ] outside array"))?;
+                args.push(array);
+            } else if TYPE_TAGS.contains(tag) {
+                args.push(Tag::Single(tag));
+            } else {
+                return Err(OscError::BadArg(format!(
+                    "Type tag \"{}\" is not implemented!",
+                    tag
+                )));
+            }
+        }
+        if !stack.is_empty() {
+            return Err(OscError::BadMessage("Encountered [ without closing ]"));
+        }
+
+        let mut tags = String::from(",");
+        args.iter().for_each(|tag| tag.push_to(&mut tags));
+        Ok(TypeSignature { tags, args })
+    }
+
+    /// Returns the type tag string, including the leading `,`.
+    pub fn as_str(&self) -> &str {
+        &self.tags
+    }
+
+    /// Returns the number of arguments, an array counts as one argument.
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    /// Returns `true` if the signature has no arguments.
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// Checks the arguments of `msg` against the signature.
+    pub fn check(&self, msg: &OscMessage) -> Result<(), SignatureMismatch> {
+        self.check_args(&msg.args)
+    }
+
+    /// Checks `args` against the signature.
+    pub fn check_args(&self, args: &[OscType]) -> Result<(), SignatureMismatch> {
+        for index in 0..self.args.len().max(args.len()) {
+            match (self.args.get(index), args.get(index)) {
+                (Some(tag), Some(arg)) if tag.matches(arg) => {}
+                (expected, found) => {
+                    return Err(SignatureMismatch {
+                        index,
+                        expected: expected.map(Tag::to_tags),
+                        found: found.map(|arg| {
+                            let mut tags = String::new();
+                            arg.push_type_tag(&mut tags);
+                            tags
+                        }),
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the arguments of `msg` match the signature.
+    pub fn matches(&self, msg: &OscMessage) -> bool {
+        self.check(msg).is_ok()
+    }
+}
+
+impl FromStr for TypeSignature {
+    type Err = OscError;
+
+    fn from_str(type_tags: &str) -> Result<Self, Self::Err> {
+        TypeSignature::new(type_tags)
+    }
+}
+
+impl fmt::Display for TypeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tags)
+    }
+}
+
+/// The first argument of a message that doesn't match a [`TypeSignature`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureMismatch {
+    /// The index of the argument, an array counts as one argument.
+    pub index: usize,
+    /// The expected type tag, `None` if the message has too many arguments.
+    pub expected: Option<String>,
+    /// The type tag of the argument, `None` if the message has too few
+    /// arguments.
+    pub found: Option<String>,
+}
+
+impl fmt::Display for SignatureMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.expected, &self.found) {
+            (Some(expected), Some(found)) => write!(
+                f,
+                "argument {}: expected type tag {}, found {}",
+                self.index, expected, found
+            ),
+            (Some(expected), None) => write!(
+                f,
+                "argument {}: expected type tag {}, found no argument",
+                self.index, expected
+            ),
+            (None, found) => write!(
+                f,
+                "argument {}: unexpected argument with type tag {}",
+                self.index,
+                found.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SignatureMismatch {}
+
+impl From<SignatureMismatch> for OscError {
+    fn from(mismatch: SignatureMismatch) -> Self {
+        OscError::BadArg(mismatch.to_string())
+    }
+}
@@ -1,6 +1,7 @@
 use crate::errors;
+use core::fmt;
 #[cfg(feature = "std")]
-use core::fmt::{self, Display};
+use core::fmt::Display;
 use core::{iter::FromIterator, result};
 
 #[cfg(feature = "std")]
@@ -178,6 +179,14 @@ pub enum OscType {
     Array(OscArray),
     Nil,
     Inf,
+    /// An argument with a type tag that isn't built in, see
+    /// [`DecodeOptions`](crate::decoder::DecodeOptions). `bytes` holds the
+    /// argument as it is encoded, including any size prefix and padding, so
+    /// it can be encoded again unchanged.
+    Unknown {
+        tag: char,
+        bytes: Vec<u8>,
+    },
 }
 macro_rules! value_impl {
     ($(($name:ident, $variant:ident, $ty:ty)),*) => {
@@ -218,6 +227,63 @@ impl From<(u32, u32)> for OscType {
     }
 }
 
+impl From<OscTime> for OscType {
+    fn from(time: OscTime) -> Self {
+        OscType::Time(time)
+    }
+}
+
+macro_rules! from_small_int_impl {
+    ($($ty:ty),*) => {
+        $(
+        impl From<$ty> for OscType {
+            fn from(v: $ty) -> Self {
+                OscType::Int(i32::from(v))
+            }
+        }
+        )*
+    }
+}
+from_small_int_impl!(u8, u16, i16);
+
+/// Fails for values beyond `i32::MAX`.
+impl TryFrom<u32> for OscType {
+    type Error = core::num::TryFromIntError;
+
+    fn try_from(v: u32) -> result::Result<Self, Self::Error> {
+        i32::try_from(v).map(OscType::Int)
+    }
+}
+
+/// Fails for values beyond `i32::MAX`.
+impl TryFrom<usize> for OscType {
+    type Error = core::num::TryFromIntError;
+
+    fn try_from(v: usize) -> result::Result<Self, Self::Error> {
+        i32::try_from(v).map(OscType::Int)
+    }
+}
+
+impl From<&[u8]> for OscType {
+    fn from(blob: &[u8]) -> Self {
+        OscType::Blob(blob.to_vec())
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<bytes::Bytes> for OscType {
+    fn from(blob: bytes::Bytes) -> Self {
+        OscType::Blob(blob.into())
+    }
+}
+
+/// `None` is converted to `Nil`.
+impl<T: Into<OscType>> From<Option<T>> for OscType {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(OscType::Nil, Into::into)
+    }
+}
+
 #[cfg(feature = "std")]
 impl Display for OscType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -244,6 +310,15 @@ impl Display for OscType {
             OscType::Array(v) => write!(f, "{v}"),
             OscType::Nil => f.write_str("(N)"),
             OscType::Inf => f.write_str("(I)"),
+            OscType::Unknown { tag, bytes } => {
+                write!(f, "({tag})")?;
+                if bytes.is_empty() {
+                    return Ok(());
+                }
+
+                f.write_str(" 0x")?;
+                write_hex(f, bytes)
+            }
         }
     }
 }
@@ -266,6 +341,36 @@ impl TryFrom<SystemTime> for OscType {
 }
 
 impl OscType {
+    /// Appends the type tag of the value, arrays append the tags of their
+    /// content enclosed in `[` and `]`.
+    pub(crate) fn push_type_tag(&self, tags: &mut String) {
+        let tag = match self {
+            OscType::Int(_) => 'i',
+            OscType::Long(_) => 'h',
+            OscType::Float(_) => 'f',
+            OscType::Double(_) => 'd',
+            OscType::Char(_) => 'c',
+            OscType::String(_) => 's',
+            OscType::Blob(_) => 'b',
+            OscType::Time(_) => 't',
+            OscType::Midi(_) => 'm',
+            OscType::Color(_) => 'r',
+            OscType::Bool(true) => 'T',
+            OscType::Bool(false) => 'F',
+            OscType::Nil => 'N',
+            OscType::Inf => 'I',
+            OscType::Unknown { tag, .. } => *tag,
+            OscType::Array(array) => {
+                tags.push('[');
+                for arg in &array.content {
+                    arg.push_type_tag(tags);
+                }
+                ']'
+            }
+        };
+        tags.push(tag);
+    }
+
     pub fn time(self) -> Option<OscTime> {
         match self {
             OscType::Time(time) => Some(time),
@@ -273,6 +378,280 @@ impl OscType {
         }
     }
 }
+
+/// Borrowing accessors, which only match the exact variant, and coercions
+/// across the numeric variants.
+impl OscType {
+    pub fn as_int(&self) -> Option<i32> {
+        match *self {
+            OscType::Int(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_long(&self) -> Option<i64> {
+        match *self {
+            OscType::Long(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f32> {
+        match *self {
+            OscType::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_double(&self) -> Option<f64> {
+        match *self {
+            OscType::Double(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_char(&self) -> Option<char> {
+        match *self {
+            OscType::Char(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            OscType::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_blob(&self) -> Option<&[u8]> {
+        match self {
+            OscType::Blob(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_time(&self) -> Option<OscTime> {
+        match *self {
+            OscType::Time(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<&OscColor> {
+        match self {
+            OscType::Color(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_midi(&self) -> Option<&OscMidiMessage> {
+        match self {
+            OscType::Midi(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&OscArray> {
+        match self {
+            OscType::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an `Int`, `Long`, `Float`, `Double` or `Bool` as
+    /// `f64`, booleans are `1.0` or `0.0`. Longs beyond 2^53 lose precision.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            OscType::Int(v) => Some(f64::from(v)),
+            OscType::Long(v) => Some(v as f64),
+            OscType::Float(v) => Some(f64::from(v)),
+            OscType::Double(v) => Some(v),
+            OscType::Bool(v) => Some(if v { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an `Int`, `Long`, `Float`, `Double` or `Bool` as
+    /// `i64`, booleans are `1` or `0`.
+    ///
+    /// Floating point values are only converted if they are integral and in
+    /// range, so `2.0` yields `Some(2)` while `2.5` yields `None`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            OscType::Int(v) => Some(i64::from(v)),
+            OscType::Long(v) => Some(v),
+            OscType::Float(v) => float_to_i64(f64::from(v)),
+            OscType::Double(v) => float_to_i64(v),
+            OscType::Bool(v) => Some(i64::from(v)),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `Bool`, or whether an `Int` or `Long` is
+    /// non-zero.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            OscType::Bool(v) => Some(v),
+            OscType::Int(v) => Some(v != 0),
+            OscType::Long(v) => Some(v != 0),
+            _ => None,
+        }
+    }
+}
+
+/// Converts an integral float in the range of `i64`.
+fn float_to_i64(v: f64) -> Option<i64> {
+    // 2^63 is exactly representable, unlike i64::MAX
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if !(-LIMIT..LIMIT).contains(&v) {
+        return None;
+    }
+    let i = v as i64;
+    if i as f64 == v {
+        Some(i)
+    } else {
+        None
+    }
+}
+
+/// An error returned by the `TryFrom<OscType>` conversions to primitive
+/// types. The original value can be recovered with
+/// [`into_value`](OscTypeError::into_value).
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscTypeError {
+    value: OscType,
+    target: &'static str,
+    kind: OscTypeErrorKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OscTypeErrorKind {
+    WrongType,
+    NotIntegral,
+    OutOfRange,
+}
+
+impl OscTypeError {
+    fn new(value: OscType, target: &'static str, kind: OscTypeErrorKind) -> Self {
+        OscTypeError {
+            value,
+            target,
+            kind,
+        }
+    }
+
+    /// Returns the value which could not be converted.
+    pub fn into_value(self) -> OscType {
+        self.value
+    }
+}
+
+impl fmt::Display for OscTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.kind {
+            OscTypeErrorKind::WrongType => "incompatible type",
+            OscTypeErrorKind::NotIntegral => "not an integer",
+            OscTypeErrorKind::OutOfRange => "out of range",
+        };
+        write!(
+            f,
+            "cannot convert {:?} to {}: {}",
+            self.value, self.target, reason
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OscTypeError {}
+
+/// Integer conversions accept every variant supported by
+/// [`OscType::as_i64`] as long as the value fits.
+macro_rules! try_from_int_impl {
+    ($($ty:ty),*) => {
+        $(
+        impl TryFrom<OscType> for $ty {
+            type Error = OscTypeError;
+
+            fn try_from(value: OscType) -> result::Result<$ty, OscTypeError> {
+                let kind = match value.as_i64() {
+                    Some(v) => match <$ty>::try_from(v) {
+                        Ok(v) => return Ok(v),
+                        Err(_) => OscTypeErrorKind::OutOfRange,
+                    },
+                    None if value.as_f64().is_some() => OscTypeErrorKind::NotIntegral,
+                    None => OscTypeErrorKind::WrongType,
+                };
+                Err(OscTypeError::new(value, stringify!($ty), kind))
+            }
+        }
+        )*
+    }
+}
+try_from_int_impl!(i32, i64);
+
+impl TryFrom<OscType> for f64 {
+    type Error = OscTypeError;
+
+    fn try_from(value: OscType) -> result::Result<f64, OscTypeError> {
+        value
+            .as_f64()
+            .ok_or_else(|| OscTypeError::new(value, "f64", OscTypeErrorKind::WrongType))
+    }
+}
+
+/// Doubles are rounded to the nearest `f32`, values beyond its range are
+/// rejected.
+impl TryFrom<OscType> for f32 {
+    type Error = OscTypeError;
+
+    fn try_from(value: OscType) -> result::Result<f32, OscTypeError> {
+        match value.as_f64() {
+            Some(v) if v.is_finite() && (v as f32).is_infinite() => Err(OscTypeError::new(
+                value,
+                "f32",
+                OscTypeErrorKind::OutOfRange,
+            )),
+            Some(v) => Ok(v as f32),
+            None => Err(OscTypeError::new(value, "f32", OscTypeErrorKind::WrongType)),
+        }
+    }
+}
+
+impl TryFrom<OscType> for bool {
+    type Error = OscTypeError;
+
+    fn try_from(value: OscType) -> result::Result<bool, OscTypeError> {
+        value
+            .as_bool()
+            .ok_or_else(|| OscTypeError::new(value, "bool", OscTypeErrorKind::WrongType))
+    }
+}
+
+impl TryFrom<OscType> for char {
+    type Error = OscTypeError;
+
+    fn try_from(value: OscType) -> result::Result<char, OscTypeError> {
+        value
+            .as_char()
+            .ok_or_else(|| OscTypeError::new(value, "char", OscTypeErrorKind::WrongType))
+    }
+}
+
+impl TryFrom<OscType> for String {
+    type Error = OscTypeError;
+
+    fn try_from(value: OscType) -> result::Result<String, OscTypeError> {
+        match value {
+            OscType::String(v) => Ok(v),
+            value => Err(OscTypeError::new(
+                value,
+                "String",
+                OscTypeErrorKind::WrongType,
+            )),
+        }
+    }
+}
 impl<'a> From<&'a str> for OscType {
     fn from(string: &'a str) -> Self {
         OscType::String(string.to_string())
@@ -280,14 +659,35 @@ impl<'a> From<&'a str> for OscType {
 }
 /// Represents the parts of a Midi message. Mainly used for
 /// tunneling midi over a network using the OSC protocol.
+///
+/// Use [`message`](OscMidiMessage::message) for a typed view of the status
+/// and data bytes, see [`MidiMessage`](crate::midi::MidiMessage).
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct OscMidiMessage {
     pub port: u8,
     pub status: u8,
-    pub data1: u8, // maybe use an enum for data?
+    pub data1: u8,
     pub data2: u8,
 }
 
+/// Converts `[port, status, data1, data2]`, the byte order used on the wire.
+impl From<[u8; 4]> for OscMidiMessage {
+    fn from([port, status, data1, data2]: [u8; 4]) -> Self {
+        OscMidiMessage {
+            port,
+            status,
+            data1,
+            data2,
+        }
+    }
+}
+
+impl From<OscMidiMessage> for [u8; 4] {
+    fn from(midi: OscMidiMessage) -> Self {
+        [midi.port, midi.status, midi.data1, midi.data2]
+    }
+}
+
 #[cfg(feature = "std")]
 impl Display for OscMidiMessage {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -307,6 +707,120 @@ pub enum OscPacket {
     Bundle(OscBundle),
 }
 
+impl From<OscMessage> for OscPacket {
+    fn from(msg: OscMessage) -> Self {
+        OscPacket::Message(msg)
+    }
+}
+
+impl From<OscBundle> for OscPacket {
+    fn from(bundle: OscBundle) -> Self {
+        OscPacket::Bundle(bundle)
+    }
+}
+
+impl OscPacket {
+    /// Returns an iterator over all messages of the packet, flattening nested
+    /// bundles depth-first.
+    ///
+    /// Each message is yielded together with its effective time tag, which is
+    /// the time tag of the innermost enclosing bundle or `None` for a packet
+    /// which is a message itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rosc::{OscBundle, OscPacket, OscTime};
+    ///
+    /// let packet = OscPacket::Bundle(
+    ///     OscBundle::at((1, 0))
+    ///         .message("/a")
+    ///         .bundle(OscBundle::at((2, 0)).message("/b"))
+    ///         .message("/c"),
+    /// );
+    /// let messages: Vec<(&str, Option<OscTime>)> = packet
+    ///     .messages()
+    ///     .map(|(msg, timetag)| (msg.addr.as_str(), timetag))
+    ///     .collect();
+    /// assert_eq!(
+    ///     messages,
+    ///     vec![
+    ///         ("/a", Some((1, 0).into())),
+    ///         ("/b", Some((2, 0).into())),
+    ///         ("/c", Some((1, 0).into())),
+    ///     ]
+    /// );
+    /// ```
+    pub fn messages(&self) -> Messages<'_> {
+        Messages {
+            stack: vec![(None, core::slice::from_ref(self).iter())],
+        }
+    }
+
+    /// Like [`messages`](OscPacket::messages), but consumes the packet and
+    /// yields owned messages.
+    pub fn into_messages(self) -> IntoMessages {
+        IntoMessages {
+            stack: vec![(None, vec![self].into_iter())],
+        }
+    }
+}
+
+/// An iterator over the messages of an [`OscPacket`] and their effective
+/// time tags, see [`OscPacket::messages`].
+#[derive(Clone, Debug)]
+pub struct Messages<'a> {
+    // The packets left to visit at each nesting level with their time tag
+    stack: Vec<(Option<OscTime>, core::slice::Iter<'a, OscPacket>)>,
+}
+
+impl<'a> Iterator for Messages<'a> {
+    type Item = (&'a OscMessage, Option<OscTime>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (timetag, packets) = self.stack.last_mut()?;
+            let timetag = *timetag;
+            match packets.next() {
+                Some(OscPacket::Message(msg)) => return Some((msg, timetag)),
+                Some(OscPacket::Bundle(bundle)) => self
+                    .stack
+                    .push((Some(bundle.timetag), bundle.content.iter())),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the owned messages of an [`OscPacket`] and their
+/// effective time tags, see [`OscPacket::into_messages`].
+#[derive(Clone, Debug)]
+pub struct IntoMessages {
+    stack: Vec<(Option<OscTime>, crate::alloc::vec::IntoIter<OscPacket>)>,
+}
+
+impl Iterator for IntoMessages {
+    type Item = (OscMessage, Option<OscTime>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (timetag, packets) = self.stack.last_mut()?;
+            let timetag = *timetag;
+            match packets.next() {
+                Some(OscPacket::Message(msg)) => return Some((msg, timetag)),
+                Some(OscPacket::Bundle(bundle)) => self
+                    .stack
+                    .push((Some(bundle.timetag), bundle.content.into_iter())),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
 #[cfg(feature = "std")]
 impl Display for OscPacket {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -329,6 +843,82 @@ pub struct OscMessage {
     pub args: Vec<OscType>,
 }
 
+impl OscMessage {
+    /// Creates a message without arguments, add arguments with
+    /// [`arg`](OscMessage::arg) and [`array`](OscMessage::array).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rosc::{OscArray, OscMessage, OscType};
+    ///
+    /// let msg = OscMessage::new("/synth/1").arg(1).arg(0.5f32).array([1, 2]);
+    /// assert_eq!(
+    ///     msg,
+    ///     OscMessage {
+    ///         addr: "/synth/1".to_string(),
+    ///         args: vec![
+    ///             OscType::Int(1),
+    ///             OscType::Float(0.5),
+    ///             OscType::Array(OscArray::from_iter([1, 2])),
+    ///         ],
+    ///     }
+    /// );
+    /// ```
+    pub fn new<A: Into<String>>(addr: A) -> Self {
+        OscMessage {
+            addr: addr.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Like [`new`](OscMessage::new), but returns an [`OscError::BadAddress`](crate::OscError::BadAddress)
+    /// if `addr` is not a valid OSC address, see [`verify_address`](crate::address::verify_address).
+    #[cfg(feature = "std")]
+    pub fn new_checked<A: Into<String>>(addr: A) -> Result<Self> {
+        let addr = addr.into();
+        crate::address::verify_address(&addr)?;
+        Ok(OscMessage::new(addr))
+    }
+
+    /// Appends an argument.
+    pub fn arg<T: Into<OscType>>(mut self, arg: T) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Appends an array argument containing the given values.
+    pub fn array<T: Into<OscType>, I: IntoIterator<Item = T>>(self, content: I) -> Self {
+        self.arg(OscType::Array(content.into_iter().collect()))
+    }
+
+    /// Returns the type tag string of the arguments as the encoder emits it,
+    /// including the leading `,`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rosc::{OscArray, OscMessage, OscType};
+    ///
+    /// let msg = OscMessage {
+    ///     addr: "/pos".to_string(),
+    ///     args: vec![
+    ///         OscType::Int(1),
+    ///         OscType::Array(OscArray::from_iter([0.5f32, 0.25])),
+    ///         OscType::Bool(false),
+    ///     ],
+    /// };
+    /// assert_eq!(msg.type_tags(), ",i[ff]F");
+    /// ```
+    pub fn type_tags(&self) -> String {
+        let mut tags = String::from(",");
+        for arg in &self.args {
+            arg.push_type_tag(&mut tags);
+        }
+        tags
+    }
+}
+
 #[cfg(feature = "std")]
 impl Display for OscMessage {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -351,6 +941,42 @@ pub struct OscBundle {
     pub content: Vec<OscPacket>,
 }
 
+impl OscBundle {
+    /// Creates an empty bundle with the given time tag, add content with
+    /// [`message`](OscBundle::message) and [`bundle`](OscBundle::bundle).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rosc::{OscBundle, OscMessage, OscPacket};
+    ///
+    /// let bundle = OscBundle::at((0, 1))
+    ///     .message(OscMessage::new("/synth/1/freq").arg(440.0f32))
+    ///     .message("/synth/1/gate")
+    ///     .bundle(OscBundle::at((10, 0)).message("/synth/2/gate"));
+    /// assert_eq!(bundle.content.len(), 3);
+    /// assert!(matches!(bundle.content[2], OscPacket::Bundle(_)));
+    /// ```
+    pub fn at<T: Into<OscTime>>(timetag: T) -> Self {
+        OscBundle {
+            timetag: timetag.into(),
+            content: Vec::new(),
+        }
+    }
+
+    /// Appends a message.
+    pub fn message<M: Into<OscMessage>>(mut self, msg: M) -> Self {
+        self.content.push(OscPacket::Message(msg.into()));
+        self
+    }
+
+    /// Appends a nested bundle.
+    pub fn bundle(mut self, bundle: OscBundle) -> Self {
+        self.content.push(OscPacket::Bundle(bundle));
+        self
+    }
+}
+
 #[cfg(feature = "std")]
 impl Display for OscBundle {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -373,6 +999,24 @@ pub struct OscColor {
     pub alpha: u8,
 }
 
+/// Converts `[red, green, blue, alpha]`.
+impl From<[u8; 4]> for OscColor {
+    fn from([red, green, blue, alpha]: [u8; 4]) -> Self {
+        OscColor {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+}
+
+impl From<OscColor> for [u8; 4] {
+    fn from(color: OscColor) -> Self {
+        [color.red, color.green, color.blue, color.alpha]
+    }
+}
+
 #[cfg(feature = "std")]
 impl Display for OscColor {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -393,6 +1037,18 @@ pub struct OscArray {
     pub content: Vec<OscType>,
 }
 
+impl<T: Into<OscType>> From<Vec<T>> for OscArray {
+    fn from(content: Vec<T>) -> Self {
+        content.into_iter().collect()
+    }
+}
+
+impl<T: Into<OscType>, const N: usize> From<[T; N]> for OscArray {
+    fn from(content: [T; N]) -> Self {
+        content.into_iter().collect()
+    }
+}
+
 impl<T: Into<OscType>> FromIterator<T> for OscArray {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> OscArray {
         OscArray {
@@ -0,0 +1,198 @@
+use crate::types::{OscArray, OscBundle, OscMessage, OscPacket, OscType};
+
+/// Traverses a packet by shared reference.
+///
+/// Each method visits an element of a packet, its default implementation
+/// visits the element's children by calling the respective `walk_*`
+/// function. Overriding implementations call the `walk_*` function
+/// themselves to continue the traversal.
+///
+/// # Example
+///
+/// ```
+/// use rosc::visitor::{self, Visitor};
+/// use rosc::{OscArray, OscBundle, OscMessage, OscPacket, OscType};
+///
+/// /// Sums up the lengths of all blobs.
+/// struct BlobSize(usize);
+///
+/// impl Visitor for BlobSize {
+///     fn visit_arg(&mut self, arg: &OscType) {
+///         if let OscType::Blob(blob) = arg {
+///             self.0 += blob.len();
+///         }
+///         visitor::walk_arg(self, arg);
+///     }
+/// }
+///
+/// let packet = OscPacket::Bundle(
+///     OscBundle::at((0, 1))
+///         .message(OscMessage::new("/a").arg(vec![1u8, 2, 3]))
+///         .message(OscMessage::new("/b").arg(OscArray::from(vec![OscType::Blob(vec![4, 5])]))),
+/// );
+/// let mut size = BlobSize(0);
+/// size.visit_packet(&packet);
+/// assert_eq!(size.0, 5);
+/// ```
+pub trait Visitor {
+    fn visit_packet(&mut self, packet: &OscPacket) {
+        walk_packet(self, packet);
+    }
+
+    fn visit_bundle(&mut self, bundle: &OscBundle) {
+        walk_bundle(self, bundle);
+    }
+
+    fn visit_message(&mut self, msg: &OscMessage) {
+        walk_message(self, msg);
+    }
+
+    /// Visits an argument of a message or an element of an array.
+    fn visit_arg(&mut self, arg: &OscType) {
+        walk_arg(self, arg);
+    }
+
+    fn visit_array(&mut self, array: &OscArray) {
+        walk_array(self, array);
+    }
+}
+
+/// Visits the message or bundle of `packet`.
+pub fn walk_packet<V: Visitor + ?Sized>(visitor: &mut V, packet: &OscPacket) {
+    match packet {
+        OscPacket::Message(msg) => visitor.visit_message(msg),
+        OscPacket::Bundle(bundle) => visitor.visit_bundle(bundle),
+    }
+}
+
+/// Visits the content of `bundle`.
+pub fn walk_bundle<V: Visitor + ?Sized>(visitor: &mut V, bundle: &OscBundle) {
+    for packet in &bundle.content {
+        visitor.visit_packet(packet);
+    }
+}
+
+/// Visits the arguments of `msg`.
+pub fn walk_message<V: Visitor + ?Sized>(visitor: &mut V, msg: &OscMessage) {
+    for arg in &msg.args {
+        visitor.visit_arg(arg);
+    }
+}
+
+/// Visits `arg` if it's an array.
+pub fn walk_arg<V: Visitor + ?Sized>(visitor: &mut V, arg: &OscType) {
+    if let OscType::Array(array) = arg {
+        visitor.visit_array(array);
+    }
+}
+
+/// Visits the content of `array`.
+pub fn walk_array<V: Visitor + ?Sized>(visitor: &mut V, array: &OscArray) {
+    for arg in &array.content {
+        visitor.visit_arg(arg);
+    }
+}
+
+/// Traverses a packet by mutable reference, to transform it in place.
+///
+/// Works like [`Visitor`], with the `walk_*_mut` functions visiting the
+/// children of an element.
+///
+/// # Example
+///
+/// ```
+/// use rosc::visitor::{self, VisitorMut};
+/// use rosc::{OscArray, OscBundle, OscMessage, OscPacket, OscType};
+///
+/// /// Converts decibels to linear gain.
+/// struct DbToGain;
+///
+/// impl VisitorMut for DbToGain {
+///     fn visit_message_mut(&mut self, msg: &mut OscMessage) {
+///         if let Some(rest) = msg.addr.strip_suffix("/db") {
+///             msg.addr = format!("{}/gain", rest);
+///             visitor::walk_message_mut(self, msg);
+///         }
+///     }
+///
+///     fn visit_arg_mut(&mut self, arg: &mut OscType) {
+///         if let OscType::Float(db) = arg {
+///             *db = 10f32.powf(*db / 20.0);
+///         }
+///         visitor::walk_arg_mut(self, arg);
+///     }
+/// }
+///
+/// let mut packet = OscPacket::Bundle(
+///     OscBundle::at((0, 1))
+///         .message(OscMessage::new("/ch/1/db").arg(OscArray::from([0.0f32, -20.0])))
+///         .message(OscMessage::new("/ch/1/pan").arg(-20.0f32)),
+/// );
+/// DbToGain.visit_packet_mut(&mut packet);
+/// assert_eq!(
+///     packet,
+///     OscPacket::Bundle(
+///         OscBundle::at((0, 1))
+///             .message(OscMessage::new("/ch/1/gain").arg(OscArray::from([1.0f32, 0.1])))
+///             .message(OscMessage::new("/ch/1/pan").arg(-20.0f32)),
+///     )
+/// );
+/// ```
+pub trait VisitorMut {
+    fn visit_packet_mut(&mut self, packet: &mut OscPacket) {
+        walk_packet_mut(self, packet);
+    }
+
+    fn visit_bundle_mut(&mut self, bundle: &mut OscBundle) {
+        walk_bundle_mut(self, bundle);
+    }
+
+    fn visit_message_mut(&mut self, msg: &mut OscMessage) {
+        walk_message_mut(self, msg);
+    }
+
+    /// Visits an argument of a message or an element of an array.
+    fn visit_arg_mut(&mut self, arg: &mut OscType) {
+        walk_arg_mut(self, arg);
+    }
+
+    fn visit_array_mut(&mut self, array: &mut OscArray) {
+        walk_array_mut(self, array);
+    }
+}
+
+/// Visits the message or bundle of `packet`.
+pub fn walk_packet_mut<V: VisitorMut + ?Sized>(visitor: &mut V, packet: &mut OscPacket) {
+    match packet {
+        OscPacket::Message(msg) => visitor.visit_message_mut(msg),
+        OscPacket::Bundle(bundle) => visitor.visit_bundle_mut(bundle),
+    }
+}
+
+/// Visits the content of `bundle`.
+pub fn walk_bundle_mut<V: VisitorMut + ?Sized>(visitor: &mut V, bundle: &mut OscBundle) {
+    for packet in &mut bundle.content {
+        visitor.visit_packet_mut(packet);
+    }
+}
+
+/// Visits the arguments of `msg`.
+pub fn walk_message_mut<V: VisitorMut + ?Sized>(visitor: &mut V, msg: &mut OscMessage) {
+    for arg in &mut msg.args {
+        visitor.visit_arg_mut(arg);
+    }
+}
+
+/// Visits `arg` if it's an array.
+pub fn walk_arg_mut<V: VisitorMut + ?Sized>(visitor: &mut V, arg: &mut OscType) {
+    if let OscType::Array(array) = arg {
+        visitor.visit_array_mut(array);
+    }
+}
+
+/// Visits the content of `array`.
+pub fn walk_array_mut<V: VisitorMut + ?Sized>(visitor: &mut V, array: &mut OscArray) {
+    for arg in &mut array.content {
+        visitor.visit_arg_mut(arg);
+    }
+}
@@ -0,0 +1,238 @@
+extern crate rosc;
+
+use rosc::{encoder, osc, OscBundle, OscPacket, OscType};
+
+use std::convert::TryFrom;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, UdpSocket};
+use std::process::{Child, ChildStdout, Command, Stdio};
+
+const CLI: &str = env!("CARGO_BIN_EXE_rosc-cli");
+
+/// Kills the wrapped process when dropped, so failing tests don't leak it.
+struct Running(Child);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Starts `rosc-cli` with the given arguments and returns the process, its
+/// stdout and the address it reported to listen on.
+fn start(args: &[&str]) -> (Running, BufReader<ChildStdout>, String) {
+    let mut child = Command::new(CLI)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start rosc-cli");
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut stderr = BufReader::new(child.stderr.take().unwrap());
+
+    let mut line = String::new();
+    stderr.read_line(&mut line).unwrap();
+    let listening = line
+        .trim()
+        .strip_prefix("Listening on ")
+        .unwrap_or_else(|| panic!("unexpected output: {:?}", line))
+        .to_string();
+    (Running(child), stdout, listening)
+}
+
+fn read_line(stdout: &mut BufReader<ChildStdout>) -> String {
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    line.trim_end().to_string()
+}
+
+#[test]
+fn test_dump_udp() {
+    let (_cli, mut stdout, listening) = start(&["dump", "udp://127.0.0.1:0"]);
+    let target = listening.strip_prefix("udp://").unwrap();
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let packet = OscPacket::from(osc!("/mixer/1/gain", 0.5f32));
+    socket
+        .send_to(&encoder::encode(&packet).unwrap(), target)
+        .unwrap();
+
+    assert_eq!(
+        read_line(&mut stdout),
+        format!("{} /mixer/1/gain, (f) 0.5", socket.local_addr().unwrap())
+    );
+}
+
+#[test]
+fn test_dump_tcp_json_with_filter() {
+    let (_cli, mut stdout, listening) = start(&[
+        "dump",
+        "--json",
+        "--filter",
+        "/mixer/*/gain",
+        "tcp://127.0.0.1:0",
+    ]);
+    let target = listening.strip_prefix("tcp://").unwrap();
+
+    let mut stream = TcpStream::connect(target).unwrap();
+    let ignored = OscPacket::from(osc!("/deck/a/play", 1));
+    let bundle = OscPacket::Bundle(OscBundle::at((1, 2)).message("/deck/a/play").message(osc!(
+        "/mixer/2/gain",
+        3,
+        true
+    )));
+    stream
+        .write_all(&encoder::encode_tcp(&ignored).unwrap())
+        .unwrap();
+    let bytes = encoder::encode(&bundle).unwrap();
+    stream
+        .write_all(&(bytes.len() as u32).to_be_bytes())
+        .unwrap();
+    stream.write_all(&bytes).unwrap();
+
+    let line = read_line(&mut stdout);
+    let expected = format!(
+        concat!(
+            r#"{{"packet":{{"content":[{{"address":"/mixer/2/gain","args":["#,
+            r#"{{"type":"i","value":3}},{{"type":"T","value":true}}]}}],"#,
+            r#""timetag":{{"fractional":2,"seconds":1}}}},"source":"{}"}}"#
+        ),
+        stream.local_addr().unwrap()
+    );
+    assert_eq!(line, expected);
+}
+
+fn send(args: &[&str]) -> std::process::Output {
+    Command::new(CLI)
+        .arg("send")
+        .args(args)
+        .output()
+        .expect("failed to run rosc-cli")
+}
+
+#[test]
+fn test_send_udp() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let target = format!("udp://{}", socket.local_addr().unwrap());
+
+    let output = send(&[
+        &target,
+        "/mixer/1/gain",
+        "f",
+        "0.5",
+        "i",
+        "-3",
+        "s",
+        "hello",
+        "[",
+        "T",
+        "N",
+        "]",
+        "b",
+        "c0ffee",
+        "r",
+        "#ff8000ff",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let mut buf = [0u8; rosc::decoder::MTU];
+    let size = socket.recv(&mut buf).unwrap();
+    let (_, packet) = rosc::decoder::decode_udp(&buf[..size]).unwrap();
+    assert_eq!(
+        packet,
+        OscPacket::from(osc!(
+            "/mixer/1/gain",
+            0.5f32,
+            -3,
+            "hello",
+            OscType::Array(
+                vec![OscType::Bool(true), OscType::Nil]
+                    .into_iter()
+                    .collect()
+            ),
+            vec![0xc0u8, 0xff, 0xee],
+            rosc::OscColor {
+                red: 255,
+                green: 128,
+                blue: 0,
+                alpha: 255
+            },
+        ))
+    );
+}
+
+#[test]
+fn test_send_tcp_bundle() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let target = format!("tcp://{}", listener.local_addr().unwrap());
+
+    let output = send(&["--delay", "1.5", &target, "/cue/go", "i", "7"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let (mut stream, _) = listener.accept().unwrap();
+    let mut bytes = Vec::new();
+    std::io::Read::read_to_end(&mut stream, &mut bytes).unwrap();
+    let (_, packet) = rosc::decoder::decode_tcp(&bytes).unwrap();
+    match packet {
+        Some(OscPacket::Bundle(bundle)) => {
+            let now = rosc::OscTime::try_from(std::time::SystemTime::now()).unwrap();
+            assert!(bundle.timetag > now);
+            assert_eq!(bundle.content, vec![OscPacket::from(osc!("/cue/go", 7))]);
+        }
+        packet => panic!("expected a bundle, got {:?}", packet),
+    }
+}
+
+#[test]
+fn test_send_slip_to_dump() {
+    let (_cli, mut stdout, listening) = start(&["dump", "slip+tcp://127.0.0.1:0"]);
+
+    // 0xc0 and 0xdb have to be escaped in SLIP frames
+    let output = send(&[&listening, "/raw", "b", "c0db00", "I"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let line = read_line(&mut stdout);
+    assert!(line.ends_with(" /raw, (b) 0xC0DB00, (I)"), "{:?}", line);
+}
+
+#[test]
+fn test_send_rejects_invalid_input() {
+    for args in [
+        &["udp://127.0.0.1:9", "mixer/1"][..],
+        &["udp://127.0.0.1:9", "/mixer", "i", "1.5"][..],
+        &["udp://127.0.0.1:9", "/mixer", "x", "1"][..],
+        &["udp://127.0.0.1:9", "/mixer", "[", "i", "1"][..],
+        &["127.0.0.1:9", "/mixer"][..],
+    ] {
+        let output = send(args);
+        assert!(!output.status.success(), "{:?} was accepted", args);
+    }
+}
+
+#[test]
+fn test_relay() {
+    let consumer = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let destination = format!("udp://{}", consumer.local_addr().unwrap());
+    let (_cli, _, listening) = start(&[
+        "relay",
+        "udp://127.0.0.1:0",
+        "--route",
+        "/deck/a/*",
+        "--to",
+        &destination,
+        "--rewrite",
+        "/deck/a",
+        "/mixer/ch/1",
+        "--coerce",
+        "0:float",
+    ]);
+
+    let output = send(&[&listening, "/deck/a/gain", "i", "1"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let mut buf = [0u8; rosc::decoder::MTU];
+    let size = consumer.recv(&mut buf).unwrap();
+    let (_, packet) = rosc::decoder::decode_udp(&buf[..size]).unwrap();
+    assert_eq!(packet, OscPacket::from(osc!("/mixer/ch/1/gain", 1.0f32)));
+}
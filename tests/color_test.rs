@@ -0,0 +1,150 @@
+extern crate rosc;
+
+#[cfg(feature = "std")]
+use rosc::color::ParseColorError;
+use rosc::OscColor;
+
+fn assert_close(actual: (f32, f32, f32), expected: (f32, f32, f32)) {
+    let close = |a: f32, b: f32| (a - b) < 1e-3 && (b - a) < 1e-3;
+    assert!(
+        close(actual.0, expected.0) && close(actual.1, expected.1) && close(actual.2, expected.2),
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn test_packed_u32() {
+    let color = OscColor::from([0x12, 0x34, 0x56, 0x78]);
+    assert_eq!(color.to_rgba_u32(), 0x12345678);
+    assert_eq!(OscColor::from_rgba_u32(0x12345678), color);
+    assert_eq!(OscColor::from(0x12345678u32), color);
+    assert_eq!(u32::from(color), 0x12345678);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_hex_strings() {
+    let color = OscColor::from([0xab, 0x01, 0xff, 0x80]);
+    assert_eq!(color.to_hex(), "#ab01ff80");
+    assert_eq!("#ab01ff80".parse::<OscColor>(), Ok(color.clone()));
+    assert_eq!("AB01FF80".parse::<OscColor>(), Ok(color));
+    assert_eq!(
+        "#ab01ff".parse::<OscColor>(),
+        Ok(OscColor::from([0xab, 0x01, 0xff, 0xff]))
+    );
+
+    for bad in [
+        "",
+        "#",
+        "#ab01f",
+        "#ab01ff8",
+        "#ab01ff800",
+        "#+b01ff80",
+        "ab01ffgg",
+        "##ab01ff",
+    ] {
+        assert_eq!(bad.parse::<OscColor>(), Err(ParseColorError), "{}", bad);
+    }
+    assert_eq!(
+        ParseColorError.to_string(),
+        "invalid color, expected #RRGGBB or #RRGGBBAA"
+    );
+}
+
+#[test]
+fn test_normalized_components() {
+    let color = OscColor::from((1.0, 0.5, 0.0, 0.25));
+    assert_eq!(color, OscColor::from([255, 128, 0, 64]));
+    assert_eq!(
+        <(f32, f32, f32, f32)>::from(OscColor::from([255, 0, 51, 0])),
+        (1.0, 0.0, 0.2, 0.0)
+    );
+    // Out of range and NaN components are clamped
+    assert_eq!(
+        OscColor::from((2.0, -1.0, f32::NAN, 1.0)),
+        OscColor::from([255, 0, 0, 255])
+    );
+    for value in 0..=255u8 {
+        let color = OscColor::from([value, value, value, value]);
+        assert_eq!(
+            OscColor::from(<(f32, f32, f32, f32)>::from(color.clone())),
+            color
+        );
+    }
+}
+
+#[test]
+fn test_hsv() {
+    assert_eq!(
+        OscColor::from_hsv(0.0, 1.0, 1.0),
+        OscColor::from([255, 0, 0, 255])
+    );
+    assert_eq!(
+        OscColor::from_hsv(120.0, 1.0, 1.0),
+        OscColor::from([0, 255, 0, 255])
+    );
+    assert_eq!(
+        OscColor::from_hsv(240.0, 1.0, 0.5),
+        OscColor::from([0, 0, 128, 255])
+    );
+    assert_eq!(
+        OscColor::from_hsv(-60.0, 1.0, 1.0),
+        OscColor::from([255, 0, 255, 255])
+    );
+    assert_eq!(
+        OscColor::from_hsv(420.0, 1.0, 1.0),
+        OscColor::from([255, 255, 0, 255])
+    );
+    assert_eq!(
+        OscColor::from_hsv(90.0, 0.0, 1.0),
+        OscColor::from([255, 255, 255, 255])
+    );
+
+    assert_close(OscColor::from([255, 0, 0, 0]).to_hsv(), (0.0, 1.0, 1.0));
+    assert_close(OscColor::from([0, 0, 255, 0]).to_hsv(), (240.0, 1.0, 1.0));
+    assert_close(OscColor::from([255, 0, 255, 0]).to_hsv(), (300.0, 1.0, 1.0));
+    assert_close(OscColor::from([0, 0, 0, 0]).to_hsv(), (0.0, 0.0, 0.0));
+    assert_close(OscColor::from([51, 51, 51, 0]).to_hsv(), (0.0, 0.0, 0.2));
+}
+
+#[test]
+fn test_hsl() {
+    assert_eq!(
+        OscColor::from_hsl(0.0, 1.0, 0.5),
+        OscColor::from([255, 0, 0, 255])
+    );
+    assert_eq!(
+        OscColor::from_hsl(180.0, 1.0, 0.25),
+        OscColor::from([0, 128, 128, 255])
+    );
+    assert_eq!(
+        OscColor::from_hsl(0.0, 1.0, 1.0),
+        OscColor::from([255, 255, 255, 255])
+    );
+    assert_eq!(
+        OscColor::from_hsl(0.0, 0.0, 0.0),
+        OscColor::from([0, 0, 0, 255])
+    );
+
+    assert_close(OscColor::from([255, 0, 0, 0]).to_hsl(), (0.0, 1.0, 0.5));
+    assert_close(OscColor::from([0, 255, 255, 0]).to_hsl(), (180.0, 1.0, 0.5));
+    assert_close(OscColor::from([255, 255, 255, 0]).to_hsl(), (0.0, 0.0, 1.0));
+}
+
+#[test]
+fn test_hsv_and_hsl_round_trip() {
+    for value in (0..=255u8).step_by(15) {
+        for color in [
+            OscColor::from([value, 255 - value, value / 2, 255]),
+            OscColor::from([255, value, 0, 255]),
+            OscColor::from([value / 3, 10, value, 255]),
+        ] {
+            let (h, s, v) = color.to_hsv();
+            assert_eq!(OscColor::from_hsv(h, s, v), color.clone());
+            let (h, s, l) = color.to_hsl();
+            assert_eq!(OscColor::from_hsl(h, s, l), color);
+        }
+    }
+}
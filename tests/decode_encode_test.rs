@@ -1,15 +1,22 @@
 #![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::approx_constant)]
 
 extern crate rosc;
 
+#[cfg(feature = "std")]
 use rosc::{decoder, encoder};
+#[cfg(feature = "std")]
 use rosc::{OscArray, OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscType};
 
 extern crate hex;
 
+#[cfg(feature = "std")]
 const GOLDEN_MESSAGE_WO_ARGS: &str = "2f736f6d652f6164647200002c000000";
+#[cfg(feature = "std")]
 const GOLDEN_MESSAGE_WITH_ALL_TYPES: &str = "2f616e6f746865722f616464726573732f3100002c69686664737362746346544e496d725b695b64645d735d0000000000000004000000000000002a40490fda400921fb54442eea54686973206973206120737472696e672e00000054686973206973206120737472696e6720746f6f2e00000000000003010203000000007b000001c80000006304292a81ffc02a0d0000002a3ff3ae147ae147ae4009ae147ae147ae59617900";
+#[cfg(feature = "std")]
 const GOLDEN_EMPTY_BUNDLE: &str = "2362756e646c65000000000400000002";
+#[cfg(feature = "std")]
 const GOLDEN_BUNDLE: &str = "2362756e646c6500000004d2000010e10000000c2f766965772f31002c000000000000202f6d697865722f6368616e6e656c2f312f616d70000000002c6600003f666666000000442362756e646c65000000162e0000223d000000142f6f73632f312f66726571002c690000000001b8000000182f6f73632f312f7068617365000000002c660000becccccd";
 
 #[cfg(feature = "std")]
@@ -278,3 +285,210 @@ fn test_decode_encoded_message_four_byte_aligned_blob_and_string() {
     let (_, decoded_packet) = decoder::decode_udp(&encoded_message).unwrap();
     assert_eq!(message, decoded_packet)
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn test_encode_checked() {
+    use encoder::Validate;
+    use rosc::OscError;
+
+    let valid = OscPacket::Bundle(
+        OscBundle::at((1, 0))
+            .message(OscMessage::new("/a").arg("text").arg('c'))
+            .bundle(OscBundle::at((1, 0)).bundle(OscBundle::at((2, 0)).message("/b"))),
+    );
+    assert_eq!(
+        encoder::encode_checked(&valid).unwrap(),
+        encoder::encode(&valid).unwrap()
+    );
+
+    let packet = OscPacket::Message(OscMessage::new("a"));
+    assert!(matches!(
+        encoder::encode_checked(&packet),
+        Err(OscError::AddressWithoutSlash(addr)) if addr == "a"
+    ));
+
+    let packet = OscPacket::Message(OscMessage::new("/a\0b"));
+    assert!(matches!(
+        encoder::encode_checked(&packet),
+        Err(OscError::InteriorNul(_))
+    ));
+
+    let msg = OscMessage::new("/a").arg(OscArray::from(["ok", "bad\0"]));
+    assert!(matches!(msg.validate(), Err(OscError::InteriorNul(s)) if s == "bad\0"));
+
+    let packet = OscPacket::Bundle(
+        OscBundle::at((2, 0)).bundle(OscBundle::at((3, 0)).bundle(OscBundle::at((2, 5)))),
+    );
+    assert!(matches!(
+        encoder::encode_checked(&packet),
+        Err(OscError::TimeTagBeforeParent { parent, nested })
+            if parent == (3, 0).into() && nested == (2, 5).into()
+    ));
+
+    assert!(matches!(
+        OscType::Char('é').validate(),
+        Err(OscError::NonAsciiChar('é'))
+    ));
+    let packet = OscPacket::Message(OscMessage::new("/a").arg('é'));
+    assert!(encoder::encode_checked(&packet).is_err());
+    // The unchecked encoder still accepts it
+    assert!(encoder::encode(&packet).is_ok());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_encode_vectored() {
+    use std::io::Write;
+
+    let blob = vec![7u8; 1001];
+    let text = "x".repeat(100);
+    let packet =
+        OscPacket::Bundle(
+            OscBundle::at((1, 2))
+                .message(OscMessage::new("/thumbnail").arg(1).arg(blob.clone()).arg(
+                    OscArray::from([OscType::String(text.clone()), "short".into()]),
+                ))
+                .bundle(OscBundle::at((3, 4)).message(OscMessage::new("/small").arg(vec![1u8, 2]))),
+        );
+
+    let slices = encoder::encode_vectored(&packet).unwrap();
+    let expected = encoder::encode(&packet).unwrap();
+    assert_eq!(slices.len(), expected.len());
+    assert_eq!(slices.to_vec(), expected);
+
+    let mut out = Vec::new();
+    assert_eq!(
+        out.write_vectored(&slices.io_slices()).unwrap(),
+        expected.len()
+    );
+    assert_eq!(out, expected);
+
+    // Large payloads are borrowed from the packet instead of being copied
+    let (blob, text) = match &packet {
+        OscPacket::Bundle(bundle) => match &bundle.content[0] {
+            OscPacket::Message(msg) => (
+                msg.args[1].as_blob().unwrap(),
+                msg.args[2].as_array().unwrap().content[0].as_str().unwrap(),
+            ),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+    let io_slices = slices.io_slices();
+    assert!(io_slices
+        .iter()
+        .any(|s| s.as_ptr() == blob.as_ptr() && s.len() == blob.len()));
+    assert!(io_slices
+        .iter()
+        .any(|s| s.as_ptr() == text.as_ptr() && s.len() == text.len()));
+    assert_eq!(io_slices.len(), 5);
+
+    let packet = OscPacket::Message(OscMessage::new("/a").arg(2.5f32));
+    let slices = encoder::encode_vectored(&packet).unwrap();
+    assert_eq!(slices.io_slices().len(), 1);
+    assert_eq!(slices.to_vec(), encoder::encode(&packet).unwrap());
+}
+
+#[cfg(feature = "std")]
+fn packet_with_all_lengths() -> OscPacket {
+    OscPacket::Bundle(
+        OscBundle::at((1, 2))
+            .message(
+                OscMessage::new("/all")
+                    .arg(1)
+                    .arg(2i64)
+                    .arg(3.0f32)
+                    .arg(4.0f64)
+                    .arg('c')
+                    .arg("abc")
+                    .arg(vec![1u8, 2, 3, 4, 5])
+                    .arg(OscType::Time((5, 6).into()))
+                    .arg(OscMidiMessage {
+                        port: 1,
+                        status: 2,
+                        data1: 3,
+                        data2: 4,
+                    })
+                    .arg(OscColor {
+                        red: 1,
+                        green: 2,
+                        blue: 3,
+                        alpha: 4,
+                    })
+                    .arg(true)
+                    .arg(OscType::Nil)
+                    .arg(OscType::Inf)
+                    .arg(OscArray::from([
+                        OscType::Int(1),
+                        OscArray::from(["x"]).into(),
+                    ]))
+                    .arg(OscType::Unknown {
+                        tag: 'é',
+                        bytes: vec![0, 0, 0, 1, 2],
+                    }),
+            )
+            .bundle(
+                OscBundle::at((3, 4))
+                    .message("/empty")
+                    .bundle(OscBundle::at((5, 6))),
+            ),
+    )
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_encoded_len() {
+    let packet = packet_with_all_lengths();
+    assert_eq!(
+        encoder::encoded_len(&packet),
+        encoder::encode(&packet).unwrap().len()
+    );
+    for len in 0..6 {
+        let packet = OscPacket::Message(OscMessage::new("/abc").arg("x".repeat(len)));
+        assert_eq!(
+            encoder::encoded_len(&packet),
+            encoder::encode(&packet).unwrap().len()
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_stream_output() {
+    // A writer which can't seek
+    struct Sink(Vec<u8>);
+
+    impl std::io::Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let packet = packet_with_all_lengths();
+    let mut out = encoder::StreamOutput(Sink(Vec::new()));
+    let n = encoder::encode_into(&packet, &mut out).unwrap();
+    assert_eq!(out.0 .0, encoder::encode(&packet).unwrap());
+    assert_eq!(n, out.0 .0.len());
+
+    let packet = OscPacket::Message(OscMessage::new("/tcp").arg("framed"));
+    let mut out = encoder::StreamOutput(Sink(Vec::new()));
+    let n = encoder::encode_into_tcp(&packet, &mut out).unwrap();
+    assert_eq!(out.0 .0, encoder::encode_tcp(&packet).unwrap());
+    assert_eq!(n, out.0 .0.len());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes_mut_output() {
+    let packet = packet_with_all_lengths();
+    let mut out = bytes::BytesMut::from(&b"prefix"[..]);
+    let n = encoder::encode_into(&packet, &mut out).unwrap();
+    assert_eq!(&out[..6], b"prefix");
+    assert_eq!(&out[6..], &encoder::encode(&packet).unwrap()[..]);
+    assert_eq!(n, out.len() - 6);
+}
@@ -0,0 +1,755 @@
+#![cfg(feature = "std")]
+
+extern crate rosc;
+
+use rosc::decoder::{self, DecodeOptions, DecodeWarning, TypeLayout};
+use rosc::{encoder, OscArray, OscBundle, OscError, OscMessage, OscPacket, OscType, PacketElement};
+
+/// Encodes a message with raw type tags and argument data.
+fn raw_message(addr: &str, type_tags: &str, data: &[u8]) -> Vec<u8> {
+    let mut bytes = encoder::encode_string(addr);
+    bytes.extend(encoder::encode_string(type_tags));
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+fn args(packet: OscPacket) -> Vec<OscType> {
+    match packet {
+        OscPacket::Message(msg) => msg.args,
+        packet => panic!("unexpected packet: {:?}", packet),
+    }
+}
+
+#[test]
+fn test_unknown_tag_is_rejected_by_default() {
+    let bytes = raw_message("/ext", ",iS", b"\0\0\0\x01sym\0");
+    assert!(matches!(
+        decoder::decode_udp(&bytes),
+        Err(OscError::BadArg(_))
+    ));
+    assert!(matches!(
+        decoder::decode_udp_with(&bytes, &DecodeOptions::new()).map_err(OscError::from),
+        Err(OscError::BadArg(_))
+    ));
+}
+
+#[test]
+fn test_extension_layouts() {
+    let options = DecodeOptions::new()
+        .extension('S', TypeLayout::String)
+        .extension('$', TypeLayout::Fixed(6))
+        .extension('B', TypeLayout::Sized)
+        .extension('z', TypeLayout::Fixed(0));
+    let data = [
+        &b"symbol\0\0"[..],
+        b"\x01\x02\x03\x04\x05\x06\0\0",
+        b"\0\0\0\x03abc\0",
+        b"\0\0\0\x2a",
+    ]
+    .concat();
+    let bytes = raw_message("/ext", ",S$Bzi", &data);
+
+    let (remainder, packet) = decoder::decode_udp_with(&bytes, &options).unwrap();
+    assert!(remainder.is_empty());
+    let unknown = |tag, bytes: &[u8]| OscType::Unknown {
+        tag,
+        bytes: bytes.to_vec(),
+    };
+    assert_eq!(
+        args(packet.clone()),
+        vec![
+            unknown('S', b"symbol\0\0"),
+            unknown('$', b"\x01\x02\x03\x04\x05\x06\0\0"),
+            unknown('B', b"\0\0\0\x03abc\0"),
+            unknown('z', b""),
+            OscType::Int(42),
+        ]
+    );
+    assert_eq!(encoder::encode(&packet).unwrap(), bytes);
+
+    // Truncated argument data
+    assert!(decoder::decode_udp_with(&bytes[..bytes.len() - 8], &options).is_err());
+}
+
+#[test]
+fn test_extension_decode_function() {
+    let options = DecodeOptions::new()
+        .extension_with('S', TypeLayout::String, |payload| {
+            String::from_utf8(payload.to_vec())
+                .map(OscType::String)
+                .map_err(OscError::StringError)
+        })
+        .extension_with('u', TypeLayout::Fixed(4), |_| {
+            Err(OscError::BadArg("unsupported".to_string()))
+        });
+
+    let bytes = raw_message("/ext", ",[SS]", b"ab\0\0cdef\0\0\0\0");
+    let (_, packet) = decoder::decode_udp_with(&bytes, &options).unwrap();
+    assert_eq!(
+        args(packet),
+        vec![OscType::Array(OscArray::from_iter(["ab", "cdef"]))]
+    );
+
+    let bytes = raw_message("/ext", ",u", b"\0\0\0\0");
+    assert!(matches!(
+        decoder::decode_udp_with(&bytes, &options).map_err(OscError::from),
+        Err(OscError::BadArg(msg)) if msg == "unsupported"
+    ));
+}
+
+#[test]
+fn test_builtin_tags_are_not_overridden() {
+    let options = DecodeOptions::new().extension('i', TypeLayout::Fixed(8));
+    let bytes = raw_message("/int", ",i", b"\0\0\0\x07");
+    let (_, packet) = decoder::decode_udp_with(&bytes, &options).unwrap();
+    assert_eq!(args(packet), vec![OscType::Int(7)]);
+}
+
+#[test]
+fn test_passthrough_unknown() {
+    let options = DecodeOptions::new()
+        .extension('S', TypeLayout::String)
+        .passthrough_unknown(true);
+    let data = [&b"\0\0\0\x01sym\0"[..], b"\x01\x02\x03\x04\0\0\0\x02"].concat();
+    let bytes = raw_message("/ext", ",iSX[fi]s", &data);
+
+    let (remainder, packet) = decoder::decode_udp_with(&bytes, &options).unwrap();
+    assert!(remainder.is_empty());
+    let empty = |tag| OscType::Unknown { tag, bytes: vec![] };
+    assert_eq!(
+        args(packet.clone()),
+        vec![
+            OscType::Int(1),
+            OscType::Unknown {
+                tag: 'S',
+                bytes: b"sym\0".to_vec(),
+            },
+            OscType::Unknown {
+                tag: 'X',
+                bytes: b"\x01\x02\x03\x04\0\0\0\x02".to_vec(),
+            },
+            OscType::Array(OscArray {
+                content: vec![empty('f'), empty('i')],
+            }),
+            empty('s'),
+        ]
+    );
+    assert_eq!(encoder::encode(&packet).unwrap(), bytes);
+}
+
+#[test]
+fn test_passthrough_in_bundles() {
+    let options = DecodeOptions::new().passthrough_unknown(true);
+    let unknown = raw_message("/ext", ",X", b"\x01\x02\x03\x04\x05\x06\x07\x08");
+    let known = encoder::encode(&OscPacket::Message(OscMessage::new("/known").arg(3))).unwrap();
+
+    let mut bytes = encoder::encode(&OscPacket::Bundle(OscBundle::at((1, 2)))).unwrap();
+    for element in [&unknown, &known] {
+        bytes.extend((element.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(element);
+    }
+
+    let (remainder, packet) = decoder::decode_udp_with(&bytes, &options).unwrap();
+    assert!(remainder.is_empty());
+    match &packet {
+        OscPacket::Bundle(bundle) => {
+            assert_eq!(
+                args(bundle.content[0].clone()),
+                vec![OscType::Unknown {
+                    tag: 'X',
+                    bytes: unknown[12..].to_vec(),
+                }]
+            );
+            assert_eq!(args(bundle.content[1].clone()), vec![OscType::Int(3)]);
+        }
+        packet => panic!("unexpected packet: {:?}", packet),
+    }
+    assert_eq!(encoder::encode(&packet).unwrap(), bytes);
+}
+
+#[test]
+fn test_type_layout_encode() {
+    assert_eq!(
+        TypeLayout::String.encode(b"sym").unwrap(),
+        b"sym\0".to_vec()
+    );
+    assert_eq!(
+        TypeLayout::String.encode(b"four").unwrap(),
+        b"four\0\0\0\0".to_vec()
+    );
+    assert!(TypeLayout::String.encode(b"a\0b").is_err());
+    assert_eq!(
+        TypeLayout::Sized.encode(b"abcde").unwrap(),
+        b"\0\0\0\x05abcde\0\0\0".to_vec()
+    );
+    assert_eq!(
+        TypeLayout::Fixed(2).encode(b"ab").unwrap(),
+        b"ab\0\0".to_vec()
+    );
+    assert!(TypeLayout::Fixed(2).encode(b"abc").is_err());
+    assert_eq!(TypeLayout::Fixed(0).encode(b"").unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn test_encode_unknown() {
+    let msg = OscMessage::new("/ext")
+        .arg(OscType::Unknown {
+            tag: 'S',
+            bytes: TypeLayout::String.encode(b"sym").unwrap(),
+        })
+        // Unpadded bytes are padded by the encoder
+        .arg(OscType::Unknown {
+            tag: 'u',
+            bytes: vec![1, 2],
+        });
+    assert_eq!(msg.type_tags(), ",Su");
+    assert_eq!(
+        encoder::encode(&OscPacket::Message(msg)).unwrap(),
+        raw_message("/ext", ",Su", b"sym\0\x01\x02\0\0")
+    );
+    assert_eq!(
+        OscType::Unknown {
+            tag: 'u',
+            bytes: vec![0xab, 0x01],
+        }
+        .to_string(),
+        "(u) 0xAB01"
+    );
+}
+
+#[test]
+fn test_lenient_missing_type_tags() {
+    let options = DecodeOptions::lenient();
+    let mut bytes = encoder::encode_string("/legacy");
+    assert!(decoder::decode_udp(&bytes).is_err());
+
+    let (remainder, packet, warnings) =
+        decoder::decode_udp_with_warnings(&bytes, &options).unwrap();
+    assert!(remainder.is_empty());
+    assert_eq!(packet, OscPacket::Message(OscMessage::new("/legacy")));
+    assert_eq!(warnings, vec![DecodeWarning::MissingTypeTags { offset: 8 }]);
+
+    bytes.extend_from_slice(b"\x3f\x80\0\0\0\0\0\x01");
+    assert!(decoder::decode_udp(&bytes).is_err());
+    let (remainder, packet, warnings) =
+        decoder::decode_udp_with_warnings(&bytes, &options).unwrap();
+    assert!(remainder.is_empty());
+    assert_eq!(
+        args(packet),
+        vec![OscType::Blob(b"\x3f\x80\0\0\0\0\0\x01".to_vec())]
+    );
+    assert_eq!(warnings, vec![DecodeWarning::MissingTypeTags { offset: 8 }]);
+}
+
+#[test]
+fn test_lenient_invalid_address() {
+    let bytes = raw_message("mixer/1", ",i", b"\0\0\0\x01");
+    assert!(matches!(
+        decoder::decode_udp(&bytes),
+        Err(OscError::BadPacket(_))
+    ));
+
+    let (_, packet, warnings) =
+        decoder::decode_udp_with_warnings(&bytes, &DecodeOptions::lenient()).unwrap();
+    assert_eq!(
+        packet,
+        OscPacket::Message(OscMessage::new("mixer/1").arg(1))
+    );
+    assert_eq!(
+        warnings,
+        vec![DecodeWarning::InvalidAddress {
+            offset: 0,
+            address: "mixer/1".to_string(),
+        }]
+    );
+    // Bundles are still recognized
+    let bytes = encoder::encode(&OscPacket::Bundle(OscBundle::at((0, 1)))).unwrap();
+    let (_, packet, warnings) =
+        decoder::decode_udp_with_warnings(&bytes, &DecodeOptions::lenient()).unwrap();
+    assert!(matches!(packet, OscPacket::Bundle(_)));
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_lenient_unpadded_strings() {
+    let options = DecodeOptions::lenient();
+
+    // Missing padding after the terminator
+    let bytes = raw_message("/name", ",s", b"abcdef\0");
+    assert!(decoder::decode_udp(&bytes).is_err());
+    let (remainder, packet, warnings) =
+        decoder::decode_udp_with_warnings(&bytes, &options).unwrap();
+    assert!(remainder.is_empty());
+    assert_eq!(args(packet), vec![OscType::String("abcdef".to_string())]);
+    assert_eq!(warnings, vec![DecodeWarning::MissingPadding { offset: 12 }]);
+
+    // Missing terminator
+    let bytes = raw_message("/name", ",is", b"\0\0\0\x01abcd");
+    assert!(decoder::decode_udp(&bytes).is_err());
+    let (_, packet, warnings) = decoder::decode_udp_with_warnings(&bytes, &options).unwrap();
+    assert_eq!(
+        args(packet),
+        vec![OscType::Int(1), OscType::String("abcd".to_string())]
+    );
+    assert_eq!(
+        warnings,
+        vec![DecodeWarning::UnterminatedString { offset: 16 }]
+    );
+
+    // An unpadded address without anything else
+    let (_, packet, warnings) = decoder::decode_udp_with_warnings(b"/ping", &options).unwrap();
+    assert_eq!(packet, OscPacket::Message(OscMessage::new("/ping")));
+    assert_eq!(
+        warnings,
+        vec![
+            DecodeWarning::UnterminatedString { offset: 0 },
+            DecodeWarning::MissingTypeTags { offset: 5 },
+        ]
+    );
+}
+
+#[test]
+fn test_lenient_invalid_utf8() {
+    let bytes = raw_message("/name", ",s", b"a\xffb\0");
+    assert!(matches!(
+        decoder::decode_udp(&bytes),
+        Err(OscError::StringError(_))
+    ));
+
+    let (_, packet, warnings) =
+        decoder::decode_udp_with_warnings(&bytes, &DecodeOptions::lenient()).unwrap();
+    assert_eq!(
+        args(packet),
+        vec![OscType::String("a\u{fffd}b".to_string())]
+    );
+    assert_eq!(warnings, vec![DecodeWarning::InvalidUtf8 { offset: 12 }]);
+    assert_eq!(
+        warnings[0].to_string(),
+        "invalid UTF-8 in string at offset 12"
+    );
+}
+
+#[test]
+fn test_lenient_warnings_in_bundles() {
+    let element = encoder::encode_string("/legacy");
+    let mut bytes = encoder::encode(&OscPacket::Bundle(OscBundle::at((0, 1)))).unwrap();
+    bytes.extend((element.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&element);
+
+    let (_, packet, warnings) =
+        decoder::decode_udp_with_warnings(&bytes, &DecodeOptions::lenient()).unwrap();
+    assert_eq!(
+        packet,
+        OscPacket::Bundle(OscBundle::at((0, 1)).message("/legacy"))
+    );
+    assert_eq!(
+        warnings,
+        vec![DecodeWarning::MissingTypeTags { offset: 28 }]
+    );
+}
+
+#[test]
+fn test_conforming_packets_have_no_warnings() {
+    let packet = OscPacket::Bundle(
+        OscBundle::at((3, 4))
+            .message(OscMessage::new("/a").arg("text").arg(1.5f32))
+            .message(OscMessage::new("/b")),
+    );
+    let bytes = encoder::encode(&packet).unwrap();
+    let (remainder, decoded, warnings) =
+        decoder::decode_udp_with_warnings(&bytes, &DecodeOptions::lenient()).unwrap();
+    assert!(remainder.is_empty());
+    assert_eq!(decoded, packet);
+    assert!(warnings.is_empty());
+}
+
+/// Prefixes each element with its size and appends it to an empty bundle.
+fn raw_bundle(elements: &[&[u8]]) -> Vec<u8> {
+    let mut bytes = encoder::encode(&OscPacket::Bundle(OscBundle::at((0, 1)))).unwrap();
+    for element in elements {
+        bytes.extend((element.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(element);
+    }
+    bytes
+}
+
+#[test]
+fn test_strict_accepts_conforming_packets() {
+    let packet = OscPacket::Bundle(
+        OscBundle::at((3, 4))
+            .message(
+                OscMessage::new("/mixer/*/gain")
+                    .arg("text")
+                    .arg(vec![1u8, 2, 3])
+                    .array([1, 2]),
+            )
+            .bundle(OscBundle::at((5, 6)).message("/b")),
+    );
+    let bytes = encoder::encode(&packet).unwrap();
+    let (remainder, decoded) = decoder::decode_udp_with(&bytes, &DecodeOptions::strict()).unwrap();
+    assert!(remainder.is_empty());
+    assert_eq!(decoded, packet);
+}
+
+#[test]
+fn test_strict_rejects_non_zero_padding() {
+    let strict = DecodeOptions::strict();
+    for bytes in [
+        raw_message("/str", ",s", b"ab\0x"),
+        raw_message("/blob", ",b", b"\0\0\0\x01a\0x\0"),
+        // Padding of the address
+        [&b"/pad\0\0x\0"[..], b",\0\0\0"].concat(),
+    ] {
+        assert!(decoder::decode_udp(&bytes).is_ok());
+        assert!(matches!(
+            decoder::decode_udp_with(&bytes, &strict).map_err(OscError::from),
+            Err(OscError::BadPacket("Non-zero padding"))
+        ));
+    }
+
+    let options = DecodeOptions::strict().extension('S', TypeLayout::String);
+    let bytes = raw_message("/ext", ",S", b"ab\0x");
+    assert!(matches!(
+        decoder::decode_udp_with(&bytes, &options).map_err(OscError::from),
+        Err(OscError::BadPacket("Non-zero padding"))
+    ));
+}
+
+#[test]
+fn test_strict_rejects_bad_type_tags() {
+    let strict = DecodeOptions::strict();
+
+    let bytes = raw_message("/array", ",i[i", b"\0\0\0\x01\0\0\0\x02");
+    assert!(decoder::decode_udp(&bytes).is_ok());
+    assert!(matches!(
+        decoder::decode_udp_with(&bytes, &strict).map_err(OscError::from),
+        Err(OscError::BadMessage("Encountered [ without closing ]"))
+    ));
+
+    let bytes = raw_message("/comma", "i", b"\0\0\0\x01");
+    assert!(decoder::decode_udp(&bytes).is_ok());
+    assert!(matches!(
+        decoder::decode_udp_with(&bytes, &strict).map_err(OscError::from),
+        Err(OscError::BadMessage(_))
+    ));
+}
+
+#[test]
+fn test_strict_rejects_invalid_addresses() {
+    for addr in ["/mixer/", "//gain", "/mixer/{a,b", "/a b"] {
+        let bytes = raw_message(addr, ",", b"");
+        assert!(decoder::decode_udp(&bytes).is_ok(), "{}", addr);
+        assert!(
+            matches!(
+                decoder::decode_udp_with(&bytes, &DecodeOptions::strict()).map_err(OscError::from),
+                Err(OscError::BadAddress(_))
+            ),
+            "{}",
+            addr
+        );
+    }
+}
+
+#[test]
+fn test_strict_rejects_trailing_data() {
+    let mut bytes = raw_message("/a", ",i", b"\0\0\0\x01");
+    bytes.extend_from_slice(b"\0\0\0\0");
+    let (remainder, _) = decoder::decode_udp(&bytes).unwrap();
+    assert_eq!(remainder, b"\0\0\0\0");
+    assert!(matches!(
+        decoder::decode_udp_with(&bytes, &DecodeOptions::strict()).map_err(OscError::from),
+        Err(OscError::BadPacket("Trailing data after packet"))
+    ));
+}
+
+#[test]
+fn test_strict_bundle_elements() {
+    let strict = DecodeOptions::strict();
+
+    // Element size not a multiple of 4
+    let bytes = raw_bundle(&[b"/a\0\0,s\0\0ab\0"]);
+    assert!(matches!(
+        decoder::decode_udp_with(&bytes, &strict).map_err(OscError::from),
+        Err(OscError::BadBundle(_))
+    ));
+
+    // Element with data following its packet
+    let bytes = raw_bundle(&[b"/a\0\0,\0\0\0\0\0\0\0"]);
+    let (_, packet) = decoder::decode_udp(&bytes).unwrap();
+    assert_eq!(
+        packet,
+        OscPacket::Bundle(OscBundle::at((0, 1)).message("/a"))
+    );
+    assert!(matches!(
+        decoder::decode_udp_with(&bytes, &strict).map_err(OscError::from),
+        Err(OscError::BadBundle(_))
+    ));
+
+    // Invalid elements don't silently end the bundle
+    let bytes = raw_bundle(&[b"/a\0\0,\0\0\0", b"/b\0\0,x\0\0"]);
+    let (remainder, packet) = decoder::decode_udp(&bytes).unwrap();
+    assert_eq!(
+        packet,
+        OscPacket::Bundle(OscBundle::at((0, 1)).message("/a"))
+    );
+    assert!(!remainder.is_empty());
+    assert!(matches!(
+        decoder::decode_udp_with(&bytes, &strict).map_err(OscError::from),
+        Err(OscError::BadArg(_))
+    ));
+}
+
+#[test]
+fn test_errors_locate_arguments() {
+    let options = DecodeOptions::new();
+
+    // Truncated argument
+    let bytes = raw_message("/pos", ",ff", b"\x3f\x80\0\0\x3f\x80");
+    let err = decoder::decode_udp_with(&bytes, &options).unwrap_err();
+    assert_eq!(err.offset, 16);
+    assert_eq!(err.path, vec![PacketElement::Argument(1)]);
+    assert_eq!(err.expected.as_deref(), Some("4 bytes"));
+    assert_eq!(err.found.as_deref(), Some("2 bytes"));
+    assert_eq!(
+        err.to_string(),
+        "error reading from buffer: Eof at offset 16 (argument 1): expected 4 bytes, found 2 bytes"
+    );
+
+    // Invalid UTF-8 inside a string argument
+    let bytes = raw_message("/s", ",is", b"\0\0\0\x01ab\xff\0");
+    let err = decoder::decode_udp_with(&bytes, &options).unwrap_err();
+    assert!(matches!(err.error, OscError::StringError(_)));
+    assert_eq!(err.offset, 14);
+    assert_eq!(err.path, vec![PacketElement::Argument(1)]);
+    assert_eq!(err.found.as_deref(), Some("0xff"));
+
+    // Arrays don't count as arguments
+    let bytes = raw_message("/a", ",[ib]", b"\0\0\0\x01\0\0\0\x08ab");
+    let err = decoder::decode_udp_with(&bytes, &options).unwrap_err();
+    assert_eq!(err.offset, 20);
+    assert_eq!(err.path, vec![PacketElement::Argument(1)]);
+    assert_eq!(err.expected.as_deref(), Some("8 bytes"));
+    assert_eq!(err.found.as_deref(), Some("2 bytes"));
+}
+
+#[test]
+fn test_errors_locate_type_tags_and_addresses() {
+    let options = DecodeOptions::new();
+
+    let bytes = raw_message("/ext", ",iS", b"\0\0\0\x01sym\0");
+    let err = decoder::decode_udp_with(&bytes, &options).unwrap_err();
+    assert!(matches!(err.error, OscError::BadArg(_)));
+    assert_eq!(err.offset, 10);
+    assert_eq!(err.path, vec![PacketElement::TypeTag(1)]);
+    assert_eq!(err.found.as_deref(), Some("'S'"));
+
+    let err = decoder::decode_udp_with(b"/abc", &options).unwrap_err();
+    assert_eq!(err.offset, 4);
+    assert_eq!(err.path, vec![PacketElement::Address]);
+    assert_eq!(err.expected.as_deref(), Some("null terminator"));
+    assert_eq!(err.found.as_deref(), Some("end of data"));
+
+    let err = decoder::decode_udp_with(b"abc\0", &options).unwrap_err();
+    assert_eq!(err.offset, 0);
+    assert_eq!(err.path, vec![PacketElement::Address]);
+    assert_eq!(err.found.as_deref(), Some("\"abc\""));
+
+    // Missing type tag string
+    let err = decoder::decode_udp_with(b"/abc\0\0\0\0", &options).unwrap_err();
+    assert_eq!(err.offset, 8);
+    assert_eq!(err.path, vec![PacketElement::TypeTags]);
+}
+
+#[test]
+fn test_errors_locate_bundle_elements() {
+    let strict = DecodeOptions::strict();
+
+    let bytes = raw_bundle(&[b"/a\0\0,\0\0\0", b"/b\0\0,x\0\0"]);
+    let err = decoder::decode_udp_with(&bytes, &strict).unwrap_err();
+    assert_eq!(err.offset, 37);
+    assert_eq!(
+        err.path,
+        vec![PacketElement::BundleElement(1), PacketElement::TypeTag(0)]
+    );
+    assert_eq!(
+        err.to_string(),
+        "bad OSC argument: Type tag \"x\" is not implemented! at offset 37 \
+         (bundle element 1, type tag 0): expected type tag, found 'x'"
+    );
+
+    let bytes = raw_bundle(&[b"/a\0\0,s\0\0ab\0\x01"]);
+    let err = decoder::decode_udp_with(&bytes, &strict).unwrap_err();
+    assert!(matches!(err.error, OscError::BadPacket("Non-zero padding")));
+    assert_eq!(err.offset, 31);
+    assert_eq!(
+        err.path,
+        vec![PacketElement::BundleElement(0), PacketElement::Argument(0)]
+    );
+    assert_eq!(err.found.as_deref(), Some("0x01"));
+
+    // Truncated time tag
+    let err = decoder::decode_udp_with(b"#bundle\0\0\0\0", &strict).unwrap_err();
+    assert_eq!(err.offset, 8);
+    assert_eq!(err.path, vec![PacketElement::TimeTag]);
+    assert_eq!(err.found.as_deref(), Some("3 bytes"));
+}
+
+#[test]
+fn test_default_decoder_errors_are_unchanged() {
+    use nom::error::ErrorKind;
+
+    let read_error = |bytes: &[u8]| match decoder::decode_udp(bytes) {
+        Err(OscError::ReadError(kind)) => kind,
+        result => panic!("unexpected result: {:?}", result),
+    };
+    // Unterminated address, type tags and string argument
+    assert_eq!(read_error(b"/foo"), ErrorKind::Tag);
+    assert_eq!(read_error(b"/foo\0\0\0\0,i"), ErrorKind::Tag);
+    assert_eq!(read_error(b"/foo\0\0\0\0,s\0\0abc"), ErrorKind::Tag);
+    // Truncated padding, int, blob and time tag
+    assert_eq!(read_error(b"/foo\0"), ErrorKind::Eof);
+    assert_eq!(read_error(b"/foo\0\0\0\0,i\0\0\0\0"), ErrorKind::Eof);
+    assert_eq!(
+        read_error(b"/foo\0\0\0\0,b\0\0\0\0\0\x08ab"),
+        ErrorKind::Eof
+    );
+    assert_eq!(read_error(b"#bundle\0\0\0\0"), ErrorKind::Eof);
+    assert!(matches!(
+        decoder::decode_tcp(b"\0\0"),
+        Err(OscError::ReadError(ErrorKind::Eof))
+    ));
+
+    assert!(matches!(
+        decoder::decode_udp(b""),
+        Err(OscError::BadPacket("Empty packet."))
+    ));
+    assert!(matches!(
+        decoder::decode_udp(b"foo\0"),
+        Err(OscError::BadPacket("Invalid message address or bundle tag"))
+    ));
+    assert!(matches!(
+        decoder::decode_udp(b"/fo\xff\0\0\0\0"),
+        Err(OscError::StringError(_))
+    ));
+    assert!(matches!(
+        decoder::decode_udp(b"/foo\0\0\0\0,]\0\0"),
+        Err(OscError::BadMessage("Encountered ] outside array"))
+    ));
+}
+
+fn nested_bundles(depth: usize) -> OscPacket {
+    (1..depth).fold(
+        OscPacket::Bundle(OscBundle::at((0, 1)).message("/a")),
+        |packet, _| {
+            OscPacket::Bundle(OscBundle {
+                timetag: (0, 1).into(),
+                content: vec![packet],
+            })
+        },
+    )
+}
+
+#[test]
+fn test_max_bundle_depth() {
+    let bytes = encoder::encode(&nested_bundles(3)).unwrap();
+    let options = DecodeOptions::new().max_bundle_depth(3);
+    assert_eq!(
+        decoder::decode_udp_with(&bytes, &options).unwrap().1,
+        nested_bundles(3)
+    );
+
+    // Exceeding the limit isn't mistaken for the end of the bundle
+    let options = DecodeOptions::new().max_bundle_depth(2);
+    let err = decoder::decode_udp_with(&bytes, &options).unwrap_err();
+    assert!(matches!(err.error, OscError::BundleDepthExceeded(2)));
+    assert_eq!(err.offset, 40);
+    assert_eq!(
+        err.path,
+        vec![
+            PacketElement::BundleElement(0),
+            PacketElement::BundleElement(0),
+            PacketElement::Address
+        ]
+    );
+
+    // The legacy entry points don't limit nesting
+    let bytes = encoder::encode(&nested_bundles(100)).unwrap();
+    assert_eq!(decoder::decode_udp(&bytes).unwrap().1, nested_bundles(100));
+}
+
+#[test]
+fn test_max_array_depth() {
+    let bytes = raw_message("/a", ",[[i]]", b"\0\0\0\x01");
+    assert!(decoder::decode_udp_with(&bytes, &DecodeOptions::new().max_array_depth(2)).is_ok());
+
+    let err =
+        decoder::decode_udp_with(&bytes, &DecodeOptions::new().max_array_depth(1)).unwrap_err();
+    assert!(matches!(err.error, OscError::ArrayDepthExceeded(1)));
+    assert_eq!(err.offset, 6);
+    assert_eq!(err.path, vec![PacketElement::TypeTag(1)]);
+
+    let tags = format!(",{}{}", "[".repeat(100), "]".repeat(100));
+    assert!(decoder::decode_udp(&raw_message("/a", &tags, b"")).is_ok());
+}
+
+#[test]
+fn test_max_args() {
+    let options = DecodeOptions::new().max_args(2);
+
+    // Arrays don't count as arguments
+    let bytes = raw_message("/a", ",[TF]", b"");
+    assert!(decoder::decode_udp_with(&bytes, &options).is_ok());
+
+    let bytes = raw_message("/a", ",T[FN]", b"");
+    let err = decoder::decode_udp_with(&bytes, &options).unwrap_err();
+    assert!(matches!(err.error, OscError::TooManyArgs(2)));
+    assert_eq!(err.path, vec![PacketElement::TypeTag(3)]);
+}
+
+#[test]
+fn test_max_data_len() {
+    let options = DecodeOptions::new().max_data_len(4);
+
+    let bytes = raw_message("/a", ",sb", b"abcd\0\0\0\0\0\0\0\x04abcd");
+    assert!(decoder::decode_udp_with(&bytes, &options).is_ok());
+
+    let bytes = raw_message("/a", ",s", b"abcde\0\0\0");
+    let err = decoder::decode_udp_with(&bytes, &options).unwrap_err();
+    assert!(matches!(err.error, OscError::DataTooLong(4)));
+    assert_eq!(err.offset, 8);
+    assert_eq!(err.path, vec![PacketElement::Argument(0)]);
+    assert_eq!(err.found.as_deref(), Some("5 bytes"));
+
+    let bytes = raw_message("/a", ",b", b"\0\0\0\x05abcde\0\0\0");
+    let err = decoder::decode_udp_with(&bytes, &options).unwrap_err();
+    assert!(matches!(err.error, OscError::DataTooLong(4)));
+    assert_eq!(err.offset, 8);
+
+    let bytes = raw_message("/abcde", ",", b"");
+    let err = decoder::decode_udp_with(&bytes, &options).unwrap_err();
+    assert!(matches!(err.error, OscError::DataTooLong(4)));
+    assert_eq!(err.path, vec![PacketElement::Address]);
+
+    // Lenient decoding doesn't bypass the limit
+    let err =
+        decoder::decode_udp_with(b"/a\0\0abcdefgh", &DecodeOptions::lenient().max_data_len(4))
+            .unwrap_err();
+    assert!(matches!(err.error, OscError::DataTooLong(4)));
+}
+
+#[test]
+fn test_max_allocation() {
+    let bytes = raw_message("/a", ",b", &[&b"\0\0\0\x40"[..], &[0; 64]].concat());
+    assert!(decoder::decode_udp_with(&bytes, &DecodeOptions::new().max_allocation(1024)).is_ok());
+
+    let err =
+        decoder::decode_udp_with(&bytes, &DecodeOptions::new().max_allocation(32)).unwrap_err();
+    assert!(matches!(err.error, OscError::AllocationLimitExceeded(32)));
+
+    // Room for the arguments counts before any of them is decoded
+    let bytes = raw_message("/a", &format!(",{}", "N".repeat(100)), b"");
+    let err =
+        decoder::decode_udp_with(&bytes, &DecodeOptions::new().max_allocation(1024)).unwrap_err();
+    assert!(matches!(err.error, OscError::AllocationLimitExceeded(1024)));
+    assert_eq!(err.offset, 4);
+}
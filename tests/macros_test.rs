@@ -0,0 +1,69 @@
+#![cfg(feature = "std")]
+
+extern crate rosc;
+
+use rosc::{osc, osc_bundle, OscArray, OscBundle, OscMessage, OscPacket, OscType};
+
+#[test]
+fn test_osc_macro() {
+    assert_eq!(
+        osc!(
+            "/synth/1/freq",
+            440.0f32,
+            "sine",
+            true,
+            1,
+            2i64,
+            0.5f64,
+            'c'
+        ),
+        OscMessage {
+            addr: "/synth/1/freq".to_string(),
+            args: vec![
+                OscType::Float(440.0),
+                OscType::String("sine".to_string()),
+                OscType::Bool(true),
+                OscType::Int(1),
+                OscType::Long(2),
+                OscType::Double(0.5),
+                OscType::Char('c'),
+            ],
+        }
+    );
+    let addr = String::from("/a");
+    assert_eq!(
+        osc!(addr, OscType::Nil, OscArray::from_iter([1, 2]),),
+        OscMessage {
+            addr: "/a".to_string(),
+            args: vec![
+                OscType::Nil,
+                OscType::Array(OscArray {
+                    content: vec![OscType::Int(1), OscType::Int(2)]
+                }),
+            ],
+        }
+    );
+    assert_eq!(osc!("/a"), OscMessage::from("/a"));
+}
+
+#[test]
+fn test_osc_bundle_macro() {
+    assert_eq!(osc_bundle!((1, 2)), OscBundle::at((1, 2)));
+    assert_eq!(osc_bundle!((1, 2);), OscBundle::at((1, 2)));
+    assert_eq!(
+        osc_bundle!((1, 2); osc!("/a", 1), osc_bundle!((3, 4); osc!("/b")),),
+        OscBundle {
+            timetag: (1, 2).into(),
+            content: vec![
+                OscPacket::Message(OscMessage {
+                    addr: "/a".to_string(),
+                    args: vec![OscType::Int(1)],
+                }),
+                OscPacket::Bundle(OscBundle {
+                    timetag: (3, 4).into(),
+                    content: vec![OscPacket::Message(OscMessage::from("/b"))],
+                }),
+            ],
+        }
+    );
+}
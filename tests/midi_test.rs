@@ -0,0 +1,189 @@
+#![cfg(feature = "std")]
+
+extern crate rosc;
+
+use rosc::midi::{MidiError, MidiMessage};
+use rosc::{decoder, encoder, OscMessage, OscMidiMessage, OscPacket, OscType};
+
+use std::convert::TryFrom;
+
+fn all_messages() -> Vec<MidiMessage> {
+    vec![
+        MidiMessage::NoteOff {
+            channel: 15,
+            key: 60,
+            velocity: 0,
+        },
+        MidiMessage::NoteOn {
+            channel: 0,
+            key: 127,
+            velocity: 100,
+        },
+        MidiMessage::PolyPressure {
+            channel: 3,
+            key: 64,
+            pressure: 5,
+        },
+        MidiMessage::ControlChange {
+            channel: 9,
+            controller: 7,
+            value: 127,
+        },
+        MidiMessage::ProgramChange {
+            channel: 1,
+            program: 42,
+        },
+        MidiMessage::ChannelPressure {
+            channel: 2,
+            pressure: 77,
+        },
+        MidiMessage::PitchBend {
+            channel: 4,
+            value: MidiMessage::PITCH_BEND_CENTER,
+        },
+        MidiMessage::PitchBend {
+            channel: 4,
+            value: 0x3fff,
+        },
+        MidiMessage::TimeCodeQuarterFrame(0x35),
+        MidiMessage::SongPosition(1234),
+        MidiMessage::SongSelect(3),
+        MidiMessage::TuneRequest,
+        MidiMessage::TimingClock,
+        MidiMessage::Start,
+        MidiMessage::Continue,
+        MidiMessage::Stop,
+        MidiMessage::ActiveSensing,
+        MidiMessage::SystemReset,
+    ]
+}
+
+#[test]
+fn test_to_bytes() {
+    assert_eq!(
+        MidiMessage::NoteOn {
+            channel: 2,
+            key: 60,
+            velocity: 100
+        }
+        .to_bytes(),
+        Ok([0x92, 60, 100])
+    );
+    assert_eq!(
+        MidiMessage::ProgramChange {
+            channel: 0,
+            program: 5
+        }
+        .to_bytes(),
+        Ok([0xc0, 5, 0])
+    );
+    // The 14-bit value is sent least significant byte first
+    assert_eq!(
+        MidiMessage::PitchBend {
+            channel: 0,
+            value: 0x2001
+        }
+        .to_bytes(),
+        Ok([0xe0, 0x01, 0x40])
+    );
+    assert_eq!(MidiMessage::TimingClock.to_bytes(), Ok([0xf8, 0, 0]));
+}
+
+#[test]
+fn test_round_trip_through_decoder() {
+    for (port, midi) in all_messages().into_iter().enumerate() {
+        let osc = midi.to_osc(port as u8).unwrap();
+        assert_eq!(osc.port, port as u8);
+        assert_eq!(MidiMessage::try_from(osc.clone()), Ok(midi));
+
+        let packet = OscPacket::Message(OscMessage::new("/midi").arg(osc.clone()));
+        let bytes = encoder::encode(&packet).unwrap();
+        match decoder::decode_udp(&bytes).unwrap().1 {
+            OscPacket::Message(msg) => {
+                assert_eq!(msg.args, vec![OscType::Midi(osc.clone())]);
+                assert_eq!(msg.args[0].as_midi().unwrap().message(), Ok(midi));
+            }
+            packet => panic!("unexpected packet: {:?}", packet),
+        }
+    }
+    assert_eq!(
+        OscMidiMessage::try_from(MidiMessage::Start),
+        Ok(OscMidiMessage::from([0, 0xfa, 0, 0]))
+    );
+}
+
+#[test]
+fn test_validation() {
+    // Running status
+    assert_eq!(
+        OscMidiMessage::from([0, 60, 100, 0]).message(),
+        Err(MidiError::MissingStatus(60))
+    );
+    // System exclusive and undefined status bytes
+    for status in [0xf0, 0xf4, 0xf5, 0xf7, 0xf9, 0xfd] {
+        assert_eq!(
+            MidiMessage::from_bytes([status, 0, 0]),
+            Err(MidiError::UnsupportedStatus(status))
+        );
+    }
+    assert_eq!(
+        MidiMessage::from_bytes([0x90, 60, 0x80]),
+        Err(MidiError::InvalidData(0x80))
+    );
+    assert_eq!(
+        MidiMessage::from_bytes([0xe0, 0xff, 0]),
+        Err(MidiError::InvalidData(0xff))
+    );
+    // Unused data bytes are ignored
+    assert_eq!(
+        MidiMessage::from_bytes([0xc3, 1, 0xff]),
+        Ok(MidiMessage::ProgramChange {
+            channel: 3,
+            program: 1
+        })
+    );
+
+    assert_eq!(
+        MidiMessage::NoteOn {
+            channel: 16,
+            key: 60,
+            velocity: 1
+        }
+        .to_bytes(),
+        Err(MidiError::InvalidChannel(16))
+    );
+    assert_eq!(
+        MidiMessage::ControlChange {
+            channel: 0,
+            controller: 128,
+            value: 1
+        }
+        .to_osc(0),
+        Err(MidiError::InvalidData(128))
+    );
+    assert_eq!(
+        MidiMessage::PitchBend {
+            channel: 0,
+            value: 0x4000
+        }
+        .to_bytes(),
+        Err(MidiError::ValueOutOfRange(0x4000))
+    );
+    assert_eq!(
+        MidiError::InvalidChannel(16).to_string(),
+        "invalid MIDI channel 16"
+    );
+}
+
+#[test]
+fn test_channel() {
+    assert_eq!(
+        MidiMessage::ChannelPressure {
+            channel: 7,
+            pressure: 1
+        }
+        .channel(),
+        Some(7)
+    );
+    assert_eq!(MidiMessage::Stop.channel(), None);
+}
@@ -0,0 +1,659 @@
+#![cfg(feature = "oscquery")]
+
+extern crate rosc;
+extern crate serde_json;
+
+use rosc::address::OscAddress;
+use rosc::oscquery::{
+    Access, AddressSpace, Client, Command, HostInfo, Method, Node, OscQueryError, Range, Server,
+    Transport, WebSocket, WsMessage,
+};
+use rosc::{OscArray, OscColor, OscMessage, OscPacket, OscType};
+use serde_json::{json, Value};
+
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+fn address(addr: &str) -> OscAddress {
+    OscAddress::new(addr.to_string()).unwrap()
+}
+
+fn mixer() -> AddressSpace {
+    let mut space = AddressSpace::new();
+    space
+        .add(
+            address("/mixer/1/gain"),
+            Method::new("f")
+                .access(Access::ReadWrite)
+                .range(Range::new(0.0, 1.0))
+                .description("Channel gain")
+                .value(vec![OscType::Float(0.5)]),
+        )
+        .add(
+            address("/mixer/1/mode"),
+            Method::new("s")
+                .access(Access::Read)
+                .range(Range::values(vec!["pre".into(), "post".into()]))
+                .value(vec!["post".into()]),
+        )
+        .add(
+            address("/mixer/1.5/color"),
+            Method::new(",r").value(vec![OscType::Color(OscColor {
+                red: 255,
+                green: 0,
+                blue: 16,
+                alpha: 255,
+            })]),
+        );
+    space
+}
+
+/// Sends a GET request and returns the status code and the parsed body.
+fn get(addr: SocketAddr, target: &str) -> (u16, Option<Value>) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nAccept: */*\r\n\r\n",
+        target
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    let body = if body.is_empty() {
+        None
+    } else {
+        Some(serde_json::from_str(body).unwrap())
+    };
+    (status, body)
+}
+
+fn start(space: AddressSpace) -> SocketAddr {
+    let server = Server::bind("127.0.0.1:0", HostInfo::new("mixer", 9000), space).unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.serve());
+    addr
+}
+
+#[test]
+fn test_node_json() {
+    let space = mixer();
+
+    assert_eq!(
+        space.node_json("/mixer/1/gain").unwrap(),
+        json!({
+            "FULL_PATH": "/mixer/1/gain",
+            "TYPE": "f",
+            "ACCESS": 3,
+            "RANGE": [{"MIN": 0.0, "MAX": 1.0}],
+            "DESCRIPTION": "Channel gain",
+            "VALUE": [0.5],
+        })
+    );
+    assert_eq!(
+        space.node_json("/mixer/").unwrap(),
+        json!({
+            "FULL_PATH": "/mixer",
+            "ACCESS": 0,
+            "CONTENTS": {
+                "1": {
+                    "FULL_PATH": "/mixer/1",
+                    "ACCESS": 0,
+                    "CONTENTS": {
+                        "gain": space.node_json("/mixer/1/gain").unwrap(),
+                        "mode": {
+                            "FULL_PATH": "/mixer/1/mode",
+                            "TYPE": "s",
+                            "ACCESS": 1,
+                            "RANGE": [{"VALS": ["pre", "post"]}],
+                            "VALUE": ["post"],
+                        },
+                    },
+                },
+                "1.5": {
+                    "FULL_PATH": "/mixer/1.5",
+                    "ACCESS": 0,
+                    "CONTENTS": {
+                        // Values of write-only methods are not reported
+                        "color": {
+                            "FULL_PATH": "/mixer/1.5/color",
+                            "TYPE": "r",
+                            "ACCESS": 2,
+                        },
+                    },
+                },
+            },
+        })
+    );
+    assert!(space.node_json("/mixer/2").is_none());
+    assert!(space.node_json("/mixer/1/ga").is_none());
+    assert_eq!(
+        AddressSpace::new().node_json("/").unwrap(),
+        json!({"FULL_PATH": "/", "ACCESS": 0, "CONTENTS": {}})
+    );
+}
+
+#[test]
+fn test_serve_nodes_and_attributes() {
+    let space = mixer();
+    let addr = start(space.clone());
+
+    assert_eq!(get(addr, "/"), (200, space.node_json("/")));
+    assert_eq!(
+        get(addr, "/mixer/1/gain"),
+        (200, space.node_json("/mixer/1/gain"))
+    );
+    assert_eq!(
+        get(addr, "/mixer/1/gain?VALUE"),
+        (200, Some(json!({"VALUE": [0.5]})))
+    );
+    assert_eq!(
+        get(addr, "/mixer/1/mode?RANGE"),
+        (200, Some(json!({"RANGE": [{"VALS": ["pre", "post"]}]})))
+    );
+    assert_eq!(get(addr, "/mixer/1/mode?DESCRIPTION"), (204, None));
+    assert_eq!(get(addr, "/mixer/3"), (404, None));
+}
+
+#[test]
+fn test_serve_host_info() {
+    let addr = start(AddressSpace::new());
+
+    assert_eq!(
+        get(addr, "/?HOST_INFO"),
+        (
+            200,
+            Some(json!({
+                "NAME": "mixer",
+                "OSC_IP": "127.0.0.1",
+                "OSC_PORT": 9000,
+                "OSC_TRANSPORT": "UDP",
+                "EXTENSIONS": {
+                    "ACCESS": true,
+                    "VALUE": true,
+                    "RANGE": true,
+                    "DESCRIPTION": true,
+                    "LISTEN": true,
+                },
+            }))
+        )
+    );
+}
+
+#[test]
+fn test_serve_updated_values() {
+    let server = Server::bind("127.0.0.1:0", HostInfo::new("mixer", 9000), mixer()).unwrap();
+    let addr = server.local_addr().unwrap();
+    let space = server.address_space();
+    thread::spawn(move || server.serve());
+
+    assert!(space
+        .write()
+        .unwrap()
+        .set_value("/mixer/1/gain", vec![OscType::Float(0.25)]));
+    assert!(!space.write().unwrap().set_value("/nope", vec![]));
+    assert_eq!(
+        get(addr, "/mixer/1/gain?VALUE"),
+        (200, Some(json!({"VALUE": [0.25]})))
+    );
+}
+
+#[test]
+fn test_reject_bad_requests() {
+    let addr = start(mixer());
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .write_all(b"POST /mixer HTTP/1.1\r\nContent-Length: 0\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 405 "), "{}", response);
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"garbage\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 400 "), "{}", response);
+}
+
+#[test]
+fn test_client_fetches_tree() {
+    let addr = start(mixer());
+    let client = Client::new(addr).unwrap();
+
+    let tree = client.node("/").unwrap();
+    assert_eq!(tree.full_path, "/");
+    assert!(tree.method.is_none());
+    assert_eq!(
+        tree.methods()
+            .iter()
+            .map(|node| node.full_path.as_str())
+            .collect::<Vec<_>>(),
+        vec!["/mixer/1/gain", "/mixer/1/mode", "/mixer/1.5/color"]
+    );
+    let space = mixer();
+    for node in tree.methods() {
+        let mut expected = space.get(&node.full_path).unwrap().clone();
+        // Values of write-only methods are not reported
+        if expected.access == Access::Write {
+            expected.value.clear();
+        }
+        assert_eq!(node.method.as_ref(), Some(&expected));
+    }
+    assert_eq!(
+        tree.find("/mixer/1/"),
+        client.node("/mixer/1").ok().as_ref()
+    );
+    assert!(tree.find("/mixer/2").is_none());
+    assert!(tree
+        .find("/mixer/1/gain")
+        .unwrap()
+        .find("/mixer/1")
+        .is_none());
+
+    assert_eq!(
+        client.attribute("/mixer/1/gain", "VALUE").unwrap(),
+        Some(json!([0.5]))
+    );
+    assert_eq!(
+        client.attribute("/mixer/1/mode", "DESCRIPTION").unwrap(),
+        None
+    );
+    assert!(matches!(
+        client.node("/mixer/3"),
+        Err(OscQueryError::Http(404))
+    ));
+
+    let host_info = client.host_info().unwrap();
+    assert_eq!(host_info.name, "mixer");
+    assert_eq!(host_info.osc_ip, Some(addr.ip()));
+    assert_eq!(host_info.osc_port, 9000);
+    assert_eq!(host_info.osc_transport, Transport::Udp);
+}
+
+#[test]
+fn test_client_reads_chunked_responses() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        while request.read_line(&mut line).unwrap() > 2 {
+            line.clear();
+        }
+        let body = r#"{"FULL_PATH": "/synth/freq", "TYPE": "f", "VALUE": [440.0]}"#;
+        let (first, second) = body.split_at(20);
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+             {:x}\r\n{}\r\n{:x};ext=1\r\n{}\r\n0\r\n\r\n",
+            first.len(),
+            first,
+            second.len(),
+            second
+        )
+        .unwrap();
+    });
+
+    let node = Client::new(addr).unwrap().node("/synth/freq").unwrap();
+    assert_eq!(
+        node,
+        Node {
+            full_path: "/synth/freq".to_string(),
+            // A missing ACCESS defaults to write-only
+            method: Some(Method::new("f").value(vec![OscType::Float(440.0)])),
+            contents: Default::default(),
+        }
+    );
+}
+
+#[test]
+fn test_client_encodes_paths() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sender, requests) = std::sync::mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            request.read_line(&mut line).unwrap();
+            sender.send(line.trim_end().to_string()).unwrap();
+            while request.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(stream, "HTTP/1.1 204 No Content\r\n\r\n").unwrap();
+        }
+    });
+
+    let client = Client::new(addr).unwrap();
+    assert!(client.node("/my synth/#1").is_err());
+    assert_eq!(requests.recv().unwrap(), "GET /my%20synth/%231 HTTP/1.1");
+    assert_eq!(client.attribute("/a?b/50%", "VALUE").unwrap(), None);
+    assert_eq!(requests.recv().unwrap(), "GET /a%3Fb/50%25?VALUE HTTP/1.1");
+
+    // The server decodes them again
+    let mut space = AddressSpace::new();
+    space.add(
+        address("/fx/50%"),
+        Method::new("f")
+            .access(Access::Read)
+            .value(vec![OscType::Float(0.5)]),
+    );
+    let addr = start(space);
+    let client = Client::new(addr).unwrap();
+    assert_eq!(client.node("/fx/50%").unwrap().full_path, "/fx/50%");
+    assert_eq!(
+        client.attribute("/fx/50%", "VALUE").unwrap(),
+        Some(json!([0.5]))
+    );
+    assert_eq!(get(addr, "/fx/50%zz").0, 400);
+}
+
+#[test]
+fn test_node_from_json() {
+    let node = Node::from_json(&json!({
+        "FULL_PATH": "/synth/voice",
+        "TYPE": "i[ff]r",
+        "ACCESS": 3,
+        "RANGE": [{"VALS": [1, 2, 4]}, null, null],
+        "VALUE": [2, [0.5, 1.5], "#ff0010ff"],
+    }))
+    .unwrap();
+    let method = node.method.unwrap();
+    assert_eq!(method.type_tags, "i[ff]r");
+    assert_eq!(method.access, Access::ReadWrite);
+    assert_eq!(
+        method.ranges,
+        vec![
+            Range::values(vec![OscType::Int(1), OscType::Int(2), OscType::Int(4)]),
+            Range::default(),
+            Range::default(),
+        ]
+    );
+    assert_eq!(
+        method.value,
+        vec![
+            OscType::Int(2),
+            OscType::Array(OscArray {
+                content: vec![OscType::Float(0.5), OscType::Float(1.5)]
+            }),
+            OscType::Color(OscColor {
+                red: 255,
+                green: 0,
+                blue: 16,
+                alpha: 255,
+            }),
+        ]
+    );
+
+    // A value without OSC representation doesn't discard the others
+    let node = Node::from_json(&json!({
+        "FULL_PATH": "/synth/blob",
+        "TYPE": "ibf",
+        "VALUE": [1, null, "not a float"],
+    }))
+    .unwrap();
+    assert_eq!(
+        node.method.unwrap().value,
+        vec![OscType::Int(1), OscType::Nil, OscType::Nil]
+    );
+
+    // Array values need exactly one element per type tag
+    let node = Node::from_json(&json!({
+        "FULL_PATH": "/synth/pan",
+        "TYPE": "[ff][ff]",
+        "VALUE": [[0.5], [0.5, 1.5, 2.5]],
+    }))
+    .unwrap();
+    assert_eq!(node.method.unwrap().value, vec![OscType::Nil, OscType::Nil]);
+
+    for bad in [
+        json!([]),
+        json!({"TYPE": "f"}),
+        json!({"FULL_PATH": "/a", "TYPE": 1}),
+        json!({"FULL_PATH": "/a", "TYPE": "f", "ACCESS": 7}),
+        json!({"FULL_PATH": "/a", "CONTENTS": {"b": {}}}),
+    ] {
+        assert!(
+            matches!(Node::from_json(&bad), Err(OscQueryError::BadNode(_))),
+            "{}",
+            bad
+        );
+    }
+}
+
+#[test]
+fn test_message_builder_validates_args() {
+    let tree = Node::from_json(&json!({
+        "FULL_PATH": "/",
+        "CONTENTS": {
+            "gain": {
+                "FULL_PATH": "/gain",
+                "TYPE": "f",
+                "ACCESS": 3,
+                "RANGE": [{"MIN": 0.0, "MAX": 1.0}],
+            },
+            "mode": {
+                "FULL_PATH": "/mode",
+                "TYPE": "s[ii]",
+                "ACCESS": 2,
+                "RANGE": [{"VALS": ["pre", "post"]}],
+            },
+            "level": {"FULL_PATH": "/level", "TYPE": "f", "ACCESS": 1},
+        },
+    }))
+    .unwrap();
+    let gain = tree.find("/gain").unwrap();
+    let mode = tree.find("/mode").unwrap();
+
+    assert_eq!(
+        gain.message().arg(0.5f32).build().unwrap(),
+        OscMessage {
+            addr: "/gain".to_string(),
+            args: vec![OscType::Float(0.5)],
+        }
+    );
+    assert_eq!(
+        mode.message()
+            .arg("pre")
+            .arg(OscType::Array(
+                vec![1, 2].into_iter().map(OscType::Int).collect()
+            ))
+            .build()
+            .unwrap()
+            .args
+            .len(),
+        2
+    );
+
+    let bad_arg = |result: Result<OscMessage, OscQueryError>| match result {
+        Err(OscQueryError::BadArg { index, .. }) => index,
+        other => panic!("expected a bad argument, got {:?}", other),
+    };
+    assert_eq!(bad_arg(gain.message().arg(1.5f32).build()), 0);
+    assert_eq!(bad_arg(gain.message().arg(0.5f64).build()), 0);
+    assert_eq!(bad_arg(gain.message().build()), 0);
+    assert_eq!(bad_arg(gain.message().arg(0.5f32).arg(0.5f32).build()), 1);
+    assert_eq!(
+        bad_arg(
+            mode.message()
+                .arg("solo")
+                .arg(OscType::Array(
+                    vec![1, 2].into_iter().map(OscType::Int).collect()
+                ))
+                .build()
+        ),
+        0
+    );
+    assert_eq!(
+        bad_arg(
+            mode.message()
+                .arg("pre")
+                .arg(OscType::Array(vec![OscType::Int(1)].into_iter().collect()))
+                .build()
+        ),
+        1
+    );
+    assert!(matches!(
+        tree.find("/level").unwrap().message().arg(0.5f32).build(),
+        Err(OscQueryError::NotWritable(_))
+    ));
+    assert!(matches!(
+        tree.message().build(),
+        Err(OscQueryError::NotWritable(_))
+    ));
+}
+
+#[test]
+fn test_websocket_framing() {
+    let packet = OscPacket::Message(OscMessage {
+        addr: "/mixer/1/gain".to_string(),
+        args: vec![OscType::Float(0.5)],
+    });
+    let mut client = WebSocket::client(Cursor::new(Vec::new()));
+    client.send_packet(&packet).unwrap();
+    client.listen("/mixer/1/gain").unwrap();
+    client.close().unwrap();
+    let frames = client.into_inner().into_inner();
+    // Binary frame with a masked payload
+    assert_eq!(frames[0], 0x82);
+    assert_eq!(frames[1], 0x80 | 24);
+
+    let mut server = WebSocket::server(Cursor::new(frames));
+    assert_eq!(server.recv().unwrap(), Some(WsMessage::Packet(packet)));
+    assert_eq!(
+        server.recv().unwrap(),
+        Some(WsMessage::Command(Command::Listen(
+            "/mixer/1/gain".to_string()
+        )))
+    );
+    assert_eq!(server.recv().unwrap(), None);
+
+    // A text message split into two frames with a pong in between
+    let command = br#"{"COMMAND": "IGNORE", "DATA": "/a"}"#;
+    let mut frames = vec![0x01, 10];
+    frames.extend_from_slice(&command[..10]);
+    frames.extend_from_slice(&[0x8a, 0]);
+    frames.extend_from_slice(&[0x80, (command.len() - 10) as u8]);
+    frames.extend_from_slice(&command[10..]);
+    let mut server = WebSocket::server(Cursor::new(frames));
+    assert_eq!(
+        server.recv().unwrap(),
+        Some(WsMessage::Command(Command::Ignore("/a".to_string())))
+    );
+    assert!(matches!(server.recv(), Err(OscQueryError::Io(_))));
+
+    for bad in [
+        vec![0x80, 0],
+        vec![0x81, 3, b'{', b'}', b' '],
+        vec![0x82, 3, 1, 2, 3],
+        vec![0xc2, 0],
+    ] {
+        assert!(WebSocket::server(Cursor::new(bad)).recv().is_err());
+    }
+}
+
+/// Receives the next OSC packet, skipping messages sent before the server
+/// processed a command.
+fn recv_packet(ws: &mut WebSocket<TcpStream>) -> Option<OscPacket> {
+    match ws.recv() {
+        Ok(Some(WsMessage::Packet(packet))) => Some(packet),
+        Err(OscQueryError::Io(_)) => None,
+        other => panic!("unexpected message: {:?}", other),
+    }
+}
+
+#[test]
+fn test_websocket_streams_values() {
+    let server = Server::bind("127.0.0.1:0", HostInfo::new("mixer", 9000), mixer()).unwrap();
+    let addr = server.local_addr().unwrap();
+    let updater = server.updater();
+    thread::spawn(move || server.serve());
+
+    let mut ws = WebSocket::connect(addr).unwrap();
+    ws.get_ref()
+        .set_read_timeout(Some(Duration::from_millis(50)))
+        .unwrap();
+    let gain = |value| {
+        OscPacket::Message(OscMessage {
+            addr: "/mixer/1/gain".to_string(),
+            args: vec![OscType::Float(value)],
+        })
+    };
+
+    ws.listen("/mixer/1/gain").unwrap();
+    // Commands are handled asynchronously, so retry until the first value
+    // arrives
+    let packet = (0..100)
+        .find_map(|_| {
+            assert!(updater.set_value("/mixer/1/gain", vec![OscType::Float(0.1)]));
+            recv_packet(&mut ws)
+        })
+        .unwrap();
+    assert_eq!(packet, gain(0.1));
+    while recv_packet(&mut ws).is_some() {}
+    assert!(updater.set_value("/mixer/1/gain", vec![OscType::Float(0.2)]));
+    assert_eq!(recv_packet(&mut ws), Some(gain(0.2)));
+    assert!(!updater.set_value("/nope", vec![]));
+    assert_eq!(
+        get(addr, "/mixer/1/gain?VALUE"),
+        (200, Some(json!({"VALUE": [0.2f32]})))
+    );
+
+    ws.ignore("/mixer/1/gain").unwrap();
+    ws.listen("/mixer/1/mode").unwrap();
+    let mode = OscPacket::Message(OscMessage {
+        addr: "/mixer/1/mode".to_string(),
+        args: vec!["pre".into()],
+    });
+    (0..100)
+        .find_map(|_| {
+            updater.set_value("/mixer/1/mode", vec!["pre".into()]);
+            recv_packet(&mut ws).filter(|packet| *packet == mode)
+        })
+        .unwrap();
+    while recv_packet(&mut ws).is_some() {}
+    // The IGNORE was handled before the LISTEN
+    assert!(updater.set_value("/mixer/1/gain", vec![OscType::Float(0.3)]));
+    assert!(updater.set_value("/mixer/1/mode", vec!["post".into()]));
+    assert_eq!(
+        recv_packet(&mut ws),
+        Some(OscPacket::Message(OscMessage {
+            addr: "/mixer/1/mode".to_string(),
+            args: vec!["post".into()],
+        }))
+    );
+
+    ws.close().unwrap();
+    ws.get_ref().set_read_timeout(None).unwrap();
+    assert_eq!(ws.recv().unwrap(), None);
+}
+
+#[test]
+fn test_websocket_requires_upgrade() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut request = BufReader::new(&stream);
+        let mut line = String::new();
+        while request.read_line(&mut line).unwrap() > 2 {
+            line.clear();
+        }
+        (&stream)
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+    });
+
+    assert!(matches!(
+        WebSocket::connect(addr),
+        Err(OscQueryError::WebSocket(_))
+    ));
+}
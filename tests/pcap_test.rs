@@ -0,0 +1,162 @@
+#![cfg(feature = "std")]
+
+extern crate rosc;
+
+use rosc::pcap::{CaptureError, CaptureReader, CapturedPacket};
+use rosc::{osc, osc_bundle, OscPacket, OscTime};
+
+use std::convert::TryFrom;
+use std::net::SocketAddr;
+use std::time::{Duration, UNIX_EPOCH};
+
+const PCAP: &[u8] = include_bytes!("fixtures/osc.pcap");
+const PCAPNG: &[u8] = include_bytes!("fixtures/osc.pcapng");
+
+fn time(seconds: u64, nanos: u32) -> OscTime {
+    OscTime::try_from(UNIX_EPOCH + Duration::new(seconds, nanos)).unwrap()
+}
+
+fn addr(s: &str) -> SocketAddr {
+    s.parse().unwrap()
+}
+
+/// Wraps a block body into a little-endian pcapng block.
+fn pcapng_block(block_type: u32, body: &[u8]) -> Vec<u8> {
+    let total_len = (12 + body.len()) as u32;
+    let mut block = block_type.to_le_bytes().to_vec();
+    block.extend_from_slice(&total_len.to_le_bytes());
+    block.extend_from_slice(body);
+    block.extend_from_slice(&total_len.to_le_bytes());
+    block
+}
+
+#[test]
+fn test_read_pcap() {
+    let packets = CaptureReader::new(PCAP)
+        .unwrap()
+        .ports(&[9000])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(
+        packets,
+        vec![
+            CapturedPacket {
+                time: time(1_700_000_000, 250_000_000),
+                source: addr("192.168.1.10:50000"),
+                destination: addr("192.168.1.20:9000"),
+                packet: osc!("/mixer/1/gain", 0.5f32).into(),
+            },
+            CapturedPacket {
+                time: time(1_700_000_001, 0),
+                source: addr("192.168.1.20:9000"),
+                destination: addr("192.168.1.10:50000"),
+                packet: osc_bundle!((3_900_000_000, 0); osc!("/fx/1/mix", 0.25f32)).into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_read_pcap_without_port_filter() {
+    let results = CaptureReader::new(PCAP).unwrap().collect::<Vec<_>>();
+
+    // The DNS query on port 53 is not an OSC packet
+    assert_eq!(results.len(), 4);
+    assert!(matches!(results[1], Err(CaptureError::Osc(_))));
+    assert_eq!(
+        results[3].as_ref().unwrap().packet,
+        OscPacket::from(osc!("/deck/a/play", 1))
+    );
+}
+
+#[test]
+fn test_read_pcapng() {
+    let packets = CaptureReader::new(PCAPNG)
+        .unwrap()
+        .ports(&[9000])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(
+        packets,
+        vec![
+            CapturedPacket {
+                time: time(1_700_000_000, 123_456_789),
+                source: addr("[fe80::1]:50000"),
+                destination: addr("[fe80::2]:9000"),
+                packet: osc!("/mixer/1/gain", 0.5f32).into(),
+            },
+            // Captured on a raw IP interface with the default microsecond resolution
+            CapturedPacket {
+                time: time(1_700_000_001, 0),
+                source: addr("192.168.1.10:50000"),
+                destination: addr("192.168.1.20:9000"),
+                packet: osc_bundle!((3_900_000_000, 0); osc!("/fx/1/mix", 0.25f32)).into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_reject_bad_captures() {
+    assert!(matches!(
+        CaptureReader::new(&b"\x00\x01\x02\x03"[..]),
+        Err(CaptureError::BadFormat(_))
+    ));
+    assert!(matches!(
+        CaptureReader::new(&PCAP[..10]),
+        Err(CaptureError::BadFormat(_))
+    ));
+
+    // Cut off in the middle of the first frame
+    let mut reader = CaptureReader::new(&PCAP[..60]).unwrap();
+    assert!(matches!(
+        reader.next(),
+        Some(Err(CaptureError::BadFormat(_)))
+    ));
+    assert!(reader.next().is_none());
+
+    // One tick per second and a timestamp beyond anything a `SystemTime` can hold
+    let mut capture = pcapng_block(
+        0x0a0d_0d0a,
+        &[
+            0x4d, 0x3c, 0x2b, 0x1a, 1, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ],
+    );
+    // Raw IPv4 interface with `if_tsresol` set to 10^0
+    capture.extend(pcapng_block(
+        1,
+        &[228, 0, 0, 0, 0, 0, 0, 0, 9, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    ));
+    // A UDP datagram to port 9000, stamped with almost 2^64 seconds
+    let mut datagram = vec![
+        0x45, 0, 0, 40, 0, 0, 0, 0, 64, 17, 0, 0, 127, 0, 0, 1, 127, 0, 0, 1,
+    ];
+    datagram.extend_from_slice(&[0xc3, 0x50, 0x23, 0x28, 0, 20, 0, 0]);
+    datagram.extend_from_slice(b"/x\0\0,\0\0\0");
+    let mut packet = vec![0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xff];
+    packet.extend_from_slice(&(datagram.len() as u32).to_le_bytes());
+    packet.extend_from_slice(&(datagram.len() as u32).to_le_bytes());
+    packet.extend(datagram);
+    capture.extend(pcapng_block(6, &packet));
+
+    let mut reader = CaptureReader::new(&capture[..]).unwrap();
+    assert!(matches!(
+        reader.next(),
+        Some(Err(CaptureError::BadFormat("Invalid capture timestamp")))
+    ));
+}
+
+#[test]
+fn test_unsupported_link_type() {
+    let mut capture = PCAP.to_vec();
+    // Link-layer header type in the global header
+    capture[20..24].copy_from_slice(&147u32.to_le_bytes());
+
+    let mut reader = CaptureReader::new(&capture[..]).unwrap();
+    assert!(matches!(
+        reader.next(),
+        Some(Err(CaptureError::UnsupportedLinkType(147)))
+    ));
+}
@@ -0,0 +1,235 @@
+#![cfg(feature = "std")]
+
+extern crate rosc;
+
+use rosc::recording::{Reader, Record, Recorder, RecordingError};
+use rosc::{encoder, osc, osc_bundle, OscPacket, OscTime};
+
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+fn recording(records: &[(OscTime, Option<SocketAddr>, OscPacket)]) -> Vec<u8> {
+    let mut recorder = Recorder::new(Vec::new()).unwrap();
+    for (time, source, packet) in records {
+        recorder.record(*time, *source, packet).unwrap();
+    }
+    recorder.into_inner()
+}
+
+#[test]
+fn test_record_and_read() {
+    let v4: SocketAddr = "192.168.1.10:9000".parse().unwrap();
+    let v6: SocketAddr = "[fe80::1]:57120".parse().unwrap();
+    let bundle = osc_bundle!((5, 6); osc!("/a", 1), osc!("/b", 2)).into();
+    let records = vec![
+        (OscTime::from((1, 0)), Some(v4), osc!("/x", 1).into()),
+        (OscTime::from((2, 10)), Some(v6), bundle),
+        (OscTime::from((3, 0)), None, osc!("/y", 3).into()),
+    ];
+    let log = recording(&records);
+
+    let read = Reader::new(&log[..])
+        .unwrap()
+        .collect::<Result<Vec<Record>, _>>()
+        .unwrap();
+    assert_eq!(
+        read,
+        records
+            .into_iter()
+            .map(|(time, source, packet)| Record {
+                time,
+                source,
+                packet
+            })
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_record_raw_stores_encoded_bytes() {
+    let packet = OscPacket::from(osc!("/raw", 42));
+    let bytes = encoder::encode(&packet).unwrap();
+
+    let mut recorder = Recorder::new(Vec::new()).unwrap();
+    recorder.record_raw((1, 0).into(), None, &bytes).unwrap();
+    let log = recorder.into_inner();
+
+    assert!(log.ends_with(&bytes));
+    let record = Reader::new(&log[..]).unwrap().next().unwrap().unwrap();
+    assert_eq!(record.packet, packet);
+
+    let mut recorder = Recorder::new(Vec::new()).unwrap();
+    assert!(matches!(
+        recorder.record_raw((1, 0).into(), None, b"garbage"),
+        Err(RecordingError::Osc(_))
+    ));
+}
+
+#[test]
+fn test_reader_rejects_bad_input() {
+    assert!(matches!(
+        Reader::new(&b"ROSC"[..]),
+        Err(RecordingError::BadHeader)
+    ));
+    assert!(matches!(
+        Reader::new(&b"NOTAREC\0\0\x01\0\0"[..]),
+        Err(RecordingError::BadHeader)
+    ));
+    assert!(matches!(
+        Reader::new(&b"ROSCREC\0\0\x02\0\0"[..]),
+        Err(RecordingError::UnsupportedVersion(2))
+    ));
+
+    let log = recording(&[((1, 0).into(), None, osc!("/x", 1).into())]);
+    let mut reader = Reader::new(&log[..log.len() - 2]).unwrap();
+    assert!(matches!(
+        reader.next(),
+        Some(Err(RecordingError::BadRecord(_)))
+    ));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_replay_keeps_original_timing() {
+    let log = recording(&[
+        ((10, 0).into(), None, osc!("/x", 1).into()),
+        ((10, 1 << 31).into(), None, osc!("/x", 2).into()),
+        ((12, 0).into(), None, osc!("/x", 3).into()),
+    ]);
+
+    let replayed = Reader::new(&log[..])
+        .unwrap()
+        .replay()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        replayed,
+        vec![
+            ((10, 0).into(), osc!("/x", 1).into()),
+            ((10, 1 << 31).into(), osc!("/x", 2).into()),
+            ((12, 0).into(), osc!("/x", 3).into()),
+        ]
+    );
+}
+
+#[test]
+fn test_replay_scale_and_offset() {
+    let log = recording(&[
+        ((10, 0).into(), None, osc!("/x", 1).into()),
+        ((11, 0).into(), None, osc!("/x", 2).into()),
+        ((14, 0).into(), None, osc!("/x", 3).into()),
+    ]);
+
+    let times = Reader::new(&log[..])
+        .unwrap()
+        .replay()
+        .starting_at((100, 0).into())
+        .speed(0.5)
+        .offset(Duration::from_secs(1))
+        .map(|item| item.unwrap().0)
+        .collect::<Vec<OscTime>>();
+    assert_eq!(
+        times,
+        vec![(101, 0).into(), (103, 0).into(), (109, 0).into()]
+    );
+}
+
+#[test]
+fn test_paced_replay_waits_between_packets() {
+    let log = recording(&[
+        ((10, 0).into(), None, osc!("/x", 1).into()),
+        ((10, 1 << 30).into(), None, osc!("/x", 2).into()),
+    ]);
+
+    let started = Instant::now();
+    let count = Reader::new(&log[..])
+        .unwrap()
+        .replay()
+        .speed(10.0)
+        .paced(true)
+        .count();
+    assert_eq!(count, 2);
+    // A quarter of a second at ten times the speed
+    assert!(started.elapsed() >= Duration::from_millis(25));
+}
+
+/// A reader which is interrupted before every successful read.
+struct Interrupting<'a> {
+    data: &'a [u8],
+    interrupt: bool,
+}
+
+impl std::io::Read for Interrupting<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(std::io::ErrorKind::Interrupted.into());
+        }
+        self.data.read(buf)
+    }
+}
+
+#[test]
+fn test_reader_retries_interrupted_reads() {
+    let log = recording(&[
+        ((1, 0).into(), None, osc!("/x", 1).into()),
+        ((2, 0).into(), None, osc!("/x", 2).into()),
+    ]);
+    let reader = Interrupting {
+        data: &log,
+        interrupt: false,
+    };
+    let records = Reader::new(reader)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(records.len(), 2);
+}
+
+#[test]
+fn test_reader_does_not_trust_record_length() {
+    let mut log = recording(&[]);
+    // A record claiming 4 GiB of packet data which isn't there
+    log.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 0, 0]);
+    log.extend_from_slice(&u32::MAX.to_be_bytes());
+    log.extend_from_slice(b"/x\0\0,\0\0\0");
+    assert!(matches!(
+        Reader::new(&log[..]).unwrap().next(),
+        Some(Err(RecordingError::BadRecord("Truncated record")))
+    ));
+}
+
+#[test]
+fn test_replay_rejects_times_out_of_range() {
+    let log = recording(&[
+        ((10, 0).into(), None, osc!("/x", 1).into()),
+        ((11, 0).into(), None, osc!("/x", 2).into()),
+    ]);
+    let replay = || Reader::new(&log[..]).unwrap().replay();
+
+    let times = replay()
+        .speed(1e-10)
+        .map(|item| item.map(|(time, _)| time))
+        .collect::<Vec<_>>();
+    assert!(matches!(times[0], Ok(time) if time == (10, 0).into()));
+    assert!(matches!(times[1], Err(RecordingError::TimeOutOfRange)));
+
+    // 2^32 seconds don't fit into a time tag
+    let mut offset = replay().offset(Duration::from_secs(1 << 32));
+    assert!(matches!(
+        offset.next(),
+        Some(Err(RecordingError::TimeOutOfRange))
+    ));
+    let mut offset = replay().offset(Duration::MAX);
+    assert!(matches!(
+        offset.next(),
+        Some(Err(RecordingError::TimeOutOfRange))
+    ));
+
+    let mut late = replay().starting_at((u32::MAX, 0).into());
+    assert!(late.next().unwrap().is_ok());
+    assert!(matches!(
+        late.next(),
+        Some(Err(RecordingError::TimeOutOfRange))
+    ));
+}
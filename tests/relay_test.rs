@@ -0,0 +1,209 @@
+#![cfg(feature = "std")]
+
+extern crate rosc;
+
+use rosc::relay::{ArgType, Relay, Rule};
+use rosc::{decoder, encoder, osc, OscBundle, OscPacket, OscType};
+
+use std::net::{SocketAddr, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+fn addr(port: u16) -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], port))
+}
+
+#[test]
+fn test_route_drops_unmatched_messages() {
+    let relay = Relay::new().rule(Rule::new("/mixer/*").unwrap().forward_to(addr(1)));
+
+    assert_eq!(
+        relay.route(&osc!("/mixer/gain").into()),
+        vec![(addr(1), osc!("/mixer/gain").into())]
+    );
+    assert!(relay.route(&osc!("/deck/a/play").into()).is_empty());
+    // Patterns can't be matched against other patterns
+    assert!(relay.route(&osc!("/mixer/*").into()).is_empty());
+    assert!(Relay::new().route(&osc!("/mixer/gain").into()).is_empty());
+}
+
+#[test]
+fn test_route_fans_out_to_all_matching_rules() {
+    let relay = Relay::new()
+        .rule(
+            Rule::new("/deck/{a,b}/*")
+                .unwrap()
+                .forward_to(addr(1))
+                .forward_to(addr(2)),
+        )
+        .rule(Rule::new("/deck/a/*").unwrap().forward_to(addr(3)));
+
+    let packet = osc!("/deck/a/play", 1).into();
+    assert_eq!(
+        relay.route(&packet),
+        vec![
+            (addr(1), packet.clone()),
+            (addr(2), packet.clone()),
+            (addr(3), packet),
+        ]
+    );
+}
+
+#[test]
+fn test_rewrite_prefix() {
+    let relay = Relay::new().rule(
+        Rule::new("/deck/*/*")
+            .unwrap()
+            .rewrite_prefix("/deck/a/", "/mixer/ch/1")
+            .forward_to(addr(1)),
+    );
+
+    assert_eq!(
+        relay.route(&osc!("/deck/a/gain").into()),
+        vec![(addr(1), osc!("/mixer/ch/1/gain").into())]
+    );
+    // Only whole address parts are rewritten
+    assert_eq!(
+        relay.route(&osc!("/deck/ab/gain").into()),
+        vec![(addr(1), osc!("/deck/ab/gain").into())]
+    );
+}
+
+#[test]
+fn test_coerce_args() {
+    let relay = Relay::new()
+        .rule(
+            Rule::new("/all")
+                .unwrap()
+                .coerce_args(ArgType::Float)
+                .forward_to(addr(1)),
+        )
+        .rule(
+            Rule::new("/second")
+                .unwrap()
+                .coerce_arg(1, ArgType::Int)
+                .coerce_arg(2, ArgType::String)
+                .forward_to(addr(1)),
+        );
+
+    assert_eq!(
+        relay.route(&osc!("/all", 1, 0.5f64, true, -2i64, "text").into()),
+        vec![(
+            addr(1),
+            osc!("/all", 1.0f32, 0.5f32, 1.0f32, -2.0f32, "text").into()
+        )]
+    );
+    assert_eq!(
+        relay.route(&osc!("/second", 0.5f32, 2.7f32, 3).into()),
+        vec![(addr(1), osc!("/second", 0.5f32, 2, "3").into())]
+    );
+}
+
+#[test]
+fn test_route_bundles_preserves_timetags() {
+    let relay = Relay::new()
+        .rule(Rule::new("/a/*").unwrap().forward_to(addr(1)))
+        .rule(Rule::new("/b/*").unwrap().forward_to(addr(2)));
+
+    let bundle = OscPacket::Bundle(
+        OscBundle::at((10, 20))
+            .message("/a/1")
+            .message("/b/1")
+            .message("/c/1")
+            .bundle(OscBundle::at((11, 0)).message("/a/2").message("/c/2")),
+    );
+
+    assert_eq!(
+        relay.route(&bundle),
+        vec![
+            (
+                addr(1),
+                OscBundle::at((10, 20))
+                    .message("/a/1")
+                    .bundle(OscBundle::at((11, 0)).message("/a/2"))
+                    .into()
+            ),
+            (addr(2), OscBundle::at((10, 20)).message("/b/1").into()),
+        ]
+    );
+}
+
+#[test]
+fn test_serve() {
+    let consumer = UdpSocket::bind("127.0.0.1:0").unwrap();
+    consumer
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let relay_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let relay_addr = relay_socket.local_addr().unwrap();
+
+    let relay = Relay::new().rule(
+        Rule::new("/deck/a/*")
+            .unwrap()
+            .rewrite_prefix("/deck/a", "/mixer/ch/1")
+            .forward_to(consumer.local_addr().unwrap()),
+    );
+    thread::spawn(move || relay.serve(&relay_socket));
+
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    for packet in [
+        osc!("/deck/b/gain", 0.1f32).into(),
+        osc!("/deck/a/gain", 0.5f32).into(),
+        osc!(
+            "/deck/a/cue",
+            OscType::Unknown {
+                tag: 'S',
+                bytes: b"intro\0\0\0".to_vec(),
+            }
+        )
+        .into(),
+    ] {
+        sender
+            .send_to(&encoder::encode(&packet).unwrap(), relay_addr)
+            .unwrap();
+    }
+
+    let mut buf = [0u8; decoder::MTU];
+    let size = consumer.recv(&mut buf).unwrap();
+    assert_eq!(
+        decoder::decode_udp(&buf[..size]).unwrap().1,
+        osc!("/mixer/ch/1/gain", 0.5f32).into()
+    );
+
+    // Unknown type tags are forwarded unchanged
+    let size = consumer.recv(&mut buf).unwrap();
+    let mut expected = encoder::encode_string("/mixer/ch/1/cue");
+    expected.extend_from_slice(b",S\0\0intro\0\0\0");
+    assert_eq!(&buf[..size], &expected[..]);
+}
+
+#[test]
+fn test_serve_skips_unreachable_destinations() {
+    let consumer = UdpSocket::bind("127.0.0.1:0").unwrap();
+    consumer
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let relay_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let relay_addr = relay_socket.local_addr().unwrap();
+
+    // Sending to an IPv6 address from an IPv4 socket always fails
+    let unreachable: SocketAddr = "[::1]:9".parse().unwrap();
+    let relay = Relay::new().rule(
+        Rule::new("/deck/*")
+            .unwrap()
+            .forward_to(unreachable)
+            .forward_to(consumer.local_addr().unwrap()),
+    );
+    thread::spawn(move || relay.serve(&relay_socket));
+
+    let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let mut buf = [0u8; decoder::MTU];
+    for value in 1..=2 {
+        let packet = osc!("/deck/gain", value).into();
+        sender
+            .send_to(&encoder::encode(&packet).unwrap(), relay_addr)
+            .unwrap();
+        let size = consumer.recv(&mut buf).unwrap();
+        assert_eq!(decoder::decode_udp(&buf[..size]).unwrap().1, packet);
+    }
+}
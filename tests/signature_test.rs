@@ -0,0 +1,184 @@
+#![cfg(feature = "std")]
+
+extern crate rosc;
+
+use rosc::{decoder, encoder};
+use rosc::{
+    osc, OscArray, OscColor, OscError, OscMessage, OscMidiMessage, OscPacket, OscType,
+    SignatureMismatch, TypeSignature,
+};
+
+fn all_types() -> OscMessage {
+    osc!(
+        "/method",
+        OscType::Int(1),
+        OscType::Long(2),
+        OscType::Float(3.0),
+        OscType::Double(4.0),
+        "text",
+        OscType::Blob(vec![1, 2, 3]),
+        OscType::Time((5, 6).into()),
+        OscType::Char('c'),
+        OscType::Bool(false),
+        OscType::Bool(true),
+        OscType::Nil,
+        OscType::Inf,
+        OscType::Midi(OscMidiMessage {
+            port: 1,
+            status: 0x90,
+            data1: 60,
+            data2: 100,
+        }),
+        OscType::Color(OscColor {
+            red: 1,
+            green: 2,
+            blue: 3,
+            alpha: 4,
+        }),
+        OscType::Array(OscArray {
+            content: vec![
+                OscType::Int(1),
+                OscType::Array(OscArray::from_iter([1.0f64, 2.0])),
+                "nested".into(),
+            ],
+        }),
+        OscType::Array(OscArray { content: vec![] }),
+    )
+}
+
+#[test]
+fn test_type_tags_match_encoder() {
+    let msg = all_types();
+    let tags = msg.type_tags();
+    assert_eq!(tags, ",ihfdsbtcFTNImr[i[dd]s][]");
+
+    let bytes = encoder::encode(&OscPacket::Message(msg)).unwrap();
+    let start = bytes.iter().position(|b| *b == b',').unwrap();
+    assert_eq!(&bytes[start..start + tags.len()], tags.as_bytes());
+    assert_eq!(bytes[start + tags.len()], 0);
+    assert_eq!(osc!("/method").type_tags(), ",");
+}
+
+#[test]
+fn test_parse_signature() {
+    let signature = TypeSignature::new("i[ff]s").unwrap();
+    assert_eq!(signature.as_str(), ",i[ff]s");
+    assert_eq!(signature.to_string(), ",i[ff]s");
+    assert_eq!(signature.len(), 3);
+    assert_eq!(",i[ff]s".parse::<TypeSignature>().unwrap(), signature);
+    assert!(TypeSignature::new(",").unwrap().is_empty());
+    assert!(TypeSignature::new("").unwrap().is_empty());
+    assert_eq!(TypeSignature::new("[[]]").unwrap().len(), 1);
+
+    // Signatures accept everything the decoder accepts
+    let msg = all_types();
+    let signature = TypeSignature::new(&msg.type_tags()).unwrap();
+    assert_eq!(signature.as_str(), msg.type_tags());
+    assert!(signature.matches(&msg));
+
+    for bad in ["x", "i]", "[i", "i[[f]", ",,i", "S"] {
+        assert!(TypeSignature::new(bad).is_err(), "{}", bad);
+    }
+    assert!(matches!(
+        TypeSignature::new("i]"),
+        Err(OscError::BadMessage(_))
+    ));
+    assert!(matches!(TypeSignature::new("q"), Err(OscError::BadArg(_))));
+}
+
+#[test]
+fn test_signature_matches_decoded_messages() {
+    let msg = all_types();
+    let bytes = encoder::encode(&OscPacket::Message(msg.clone())).unwrap();
+    let decoded = match decoder::decode_udp(&bytes).unwrap().1 {
+        OscPacket::Message(msg) => msg,
+        packet => panic!("unexpected packet: {:?}", packet),
+    };
+    assert_eq!(decoded.type_tags(), msg.type_tags());
+}
+
+#[test]
+fn test_check_reports_mismatches() {
+    let signature = TypeSignature::new("i[ff]T").unwrap();
+    let array = |content: Vec<OscType>| OscType::Array(OscArray { content });
+    let floats = || array(vec![OscType::Float(1.0), OscType::Float(2.0)]);
+    let mismatch = |index, expected: Option<&str>, found: Option<&str>| {
+        Err(SignatureMismatch {
+            index,
+            expected: expected.map(str::to_string),
+            found: found.map(str::to_string),
+        })
+    };
+
+    assert_eq!(
+        signature.check(&osc!(
+            "/method",
+            OscType::Int(1),
+            floats(),
+            OscType::Bool(true)
+        )),
+        Ok(())
+    );
+    // Either boolean is accepted for T and F
+    assert!(signature.matches(&osc!(
+        "/method",
+        OscType::Int(1),
+        floats(),
+        OscType::Bool(false)
+    )));
+
+    assert_eq!(
+        signature.check(&osc!(
+            "/method",
+            OscType::Long(1),
+            floats(),
+            OscType::Bool(true)
+        )),
+        mismatch(0, Some("i"), Some("h"))
+    );
+    assert_eq!(
+        signature.check(&osc!(
+            "/method",
+            OscType::Int(1),
+            array(vec![OscType::Float(1.0), OscType::Int(2)]),
+            OscType::Bool(true)
+        )),
+        mismatch(1, Some("[ff]"), Some("[fi]"))
+    );
+    assert_eq!(
+        signature.check(&osc!(
+            "/method",
+            OscType::Int(1),
+            array(vec![OscType::Float(1.0)]),
+            OscType::Bool(true)
+        )),
+        mismatch(1, Some("[ff]"), Some("[f]"))
+    );
+    assert_eq!(
+        signature.check(&osc!("/method", OscType::Int(1), OscType::Float(1.0))),
+        mismatch(1, Some("[ff]"), Some("f"))
+    );
+    assert_eq!(
+        signature.check(&osc!("/method", OscType::Int(1), floats())),
+        mismatch(2, Some("T"), None)
+    );
+    assert_eq!(
+        signature.check(&osc!(
+            "/method",
+            OscType::Int(1),
+            floats(),
+            OscType::Bool(true),
+            OscType::Nil
+        )),
+        mismatch(3, None, Some("N"))
+    );
+
+    let err = signature
+        .check_args(&[OscType::Int(1), OscType::Float(1.0)])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "argument 1: expected type tag [ff], found f"
+    );
+    assert!(matches!(OscError::from(err), OscError::BadArg(_)));
+}
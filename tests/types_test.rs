@@ -2,10 +2,10 @@
 
 extern crate rosc;
 
-use rosc::{OscArray, OscType};
-
 #[cfg(feature = "std")]
-use rosc::{OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscTime};
+use rosc::{
+    OscArray, OscBundle, OscColor, OscMessage, OscMidiMessage, OscPacket, OscTime, OscType,
+};
 
 #[cfg(feature = "std")]
 use std::{
@@ -84,7 +84,7 @@ fn osc_times_can_be_converted_to_and_from_system_times() {
 
     let mut times = vec![];
     // Sweep across a few numbers to check for tolerance
-    for seconds in vec![
+    for seconds in [
         // We don't start at zero because times before the UNIX_EPOCH cannot be converted to
         // OscTime.
         UNIX_OFFSET as u32,
@@ -322,3 +322,331 @@ fn display_osc_packet_nested_bundle() {
 fn assert_osc_type_display_eq(arg: &OscType, expected: &str) {
     assert_eq!(arg.to_string(), expected.to_string());
 }
+
+#[cfg(feature = "std")]
+#[test]
+fn test_borrowing_accessors() {
+    let string = OscType::String("text".to_string());
+    assert_eq!(string.as_str(), Some("text"));
+    assert_eq!(OscType::Int(1).as_str(), None);
+    assert_eq!(OscType::Int(1).as_int(), Some(1));
+    assert_eq!(OscType::Int(1).as_long(), None);
+    assert_eq!(OscType::Long(2).as_long(), Some(2));
+    assert_eq!(OscType::Float(0.5).as_float(), Some(0.5));
+    assert_eq!(OscType::Float(0.5).as_double(), None);
+    assert_eq!(OscType::Double(0.5).as_double(), Some(0.5));
+    assert_eq!(OscType::Char('x').as_char(), Some('x'));
+    assert_eq!(OscType::Blob(vec![1, 2]).as_blob(), Some(&[1u8, 2][..]));
+    assert_eq!(
+        OscType::Time((1, 2).into()).as_time(),
+        Some(OscTime::from((1, 2)))
+    );
+    let color = OscColor {
+        red: 1,
+        green: 2,
+        blue: 3,
+        alpha: 4,
+    };
+    assert_eq!(OscType::Color(color.clone()).as_color(), Some(&color));
+    let midi = OscMidiMessage {
+        port: 0,
+        status: 0x90,
+        data1: 60,
+        data2: 127,
+    };
+    assert_eq!(OscType::Midi(midi.clone()).as_midi(), Some(&midi));
+    let array = OscArray {
+        content: vec![OscType::Nil],
+    };
+    assert_eq!(OscType::Array(array.clone()).as_array(), Some(&array));
+    assert_eq!(OscType::Nil.as_array(), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_numeric_coercions() {
+    assert_eq!(OscType::Int(-3).as_f64(), Some(-3.0));
+    assert_eq!(OscType::Long(1 << 40).as_f64(), Some((1u64 << 40) as f64));
+    assert_eq!(OscType::Float(0.25).as_f64(), Some(0.25));
+    assert_eq!(OscType::Double(0.1).as_f64(), Some(0.1));
+    assert_eq!(OscType::Bool(true).as_f64(), Some(1.0));
+    assert_eq!(OscType::String("1".to_string()).as_f64(), None);
+
+    assert_eq!(OscType::Int(-3).as_i64(), Some(-3));
+    assert_eq!(OscType::Long(i64::MIN).as_i64(), Some(i64::MIN));
+    assert_eq!(OscType::Float(2.0).as_i64(), Some(2));
+    assert_eq!(OscType::Float(2.5).as_i64(), None);
+    assert_eq!(
+        OscType::Double(-1e18).as_i64(),
+        Some(-1_000_000_000_000_000_000)
+    );
+    assert_eq!(OscType::Double(9.3e18).as_i64(), None);
+    assert_eq!(
+        OscType::Double(-9_223_372_036_854_775_808.0).as_i64(),
+        Some(i64::MIN)
+    );
+    assert_eq!(OscType::Double(9_223_372_036_854_775_808.0).as_i64(), None);
+    assert_eq!(OscType::Double(f64::NAN).as_i64(), None);
+    assert_eq!(OscType::Bool(false).as_i64(), Some(0));
+    assert_eq!(OscType::Nil.as_i64(), None);
+
+    assert_eq!(OscType::Bool(true).as_bool(), Some(true));
+    assert_eq!(OscType::Int(0).as_bool(), Some(false));
+    assert_eq!(OscType::Long(-1).as_bool(), Some(true));
+    assert_eq!(OscType::Float(1.0).as_bool(), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_try_from_osc_type() {
+    assert_eq!(i32::try_from(OscType::Int(7)), Ok(7));
+    assert_eq!(i32::try_from(OscType::Long(7)), Ok(7));
+    assert_eq!(i32::try_from(OscType::Double(7.0)), Ok(7));
+    assert_eq!(i32::try_from(OscType::Bool(true)), Ok(1));
+    assert_eq!(i64::try_from(OscType::Long(1 << 40)), Ok(1 << 40));
+    assert_eq!(f64::try_from(OscType::Int(7)), Ok(7.0));
+    assert_eq!(f32::try_from(OscType::Double(0.5)), Ok(0.5));
+    assert_eq!(
+        f32::try_from(OscType::Double(f64::INFINITY)),
+        Ok(f32::INFINITY)
+    );
+    assert_eq!(bool::try_from(OscType::Int(1)), Ok(true));
+    assert_eq!(char::try_from(OscType::Char('c')), Ok('c'));
+    assert_eq!(
+        String::try_from(OscType::String("text".to_string())),
+        Ok("text".to_string())
+    );
+
+    let err = i32::try_from(OscType::Long(1 << 40)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "cannot convert Long(1099511627776) to i32: out of range"
+    );
+    assert_eq!(err.into_value(), OscType::Long(1 << 40));
+    assert_eq!(
+        i64::try_from(OscType::Float(0.5)).unwrap_err().to_string(),
+        "cannot convert Float(0.5) to i64: not an integer"
+    );
+    assert_eq!(
+        i32::try_from(OscType::Nil).unwrap_err().to_string(),
+        "cannot convert Nil to i32: incompatible type"
+    );
+    assert_eq!(
+        f32::try_from(OscType::Double(1e300))
+            .unwrap_err()
+            .to_string(),
+        "cannot convert Double(1e300) to f32: out of range"
+    );
+    assert!(f64::try_from(OscType::String("1".to_string())).is_err());
+    assert!(bool::try_from(OscType::Float(1.0)).is_err());
+    assert!(char::try_from(OscType::String("c".to_string())).is_err());
+    assert_eq!(
+        String::try_from(OscType::Char('c'))
+            .unwrap_err()
+            .into_value(),
+        OscType::Char('c')
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_message_builder() {
+    let msg = OscMessage::new("/synth/1")
+        .arg(1)
+        .arg(0.5f32)
+        .arg("saw")
+        .array([1.0f64, 2.0])
+        .array(Vec::<OscType>::new());
+    assert_eq!(
+        msg,
+        OscMessage {
+            addr: "/synth/1".to_string(),
+            args: vec![
+                OscType::Int(1),
+                OscType::Float(0.5),
+                OscType::String("saw".to_string()),
+                OscType::Array(OscArray {
+                    content: vec![OscType::Double(1.0), OscType::Double(2.0)]
+                }),
+                OscType::Array(OscArray { content: vec![] }),
+            ],
+        }
+    );
+    assert_eq!(OscMessage::new("/a".to_string()), OscMessage::from("/a"));
+
+    assert_eq!(
+        OscMessage::new_checked("/synth/1").unwrap().arg(1),
+        OscMessage::new("/synth/1").arg(1)
+    );
+    for bad in ["synth", "/synth/*", "/synth/", ""] {
+        assert!(
+            matches!(
+                OscMessage::new_checked(bad),
+                Err(rosc::OscError::BadAddress(_))
+            ),
+            "{}",
+            bad
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_bundle_builder() {
+    let bundle = OscBundle::at((1, 2))
+        .message(OscMessage::new("/a").arg(1))
+        .message("/b")
+        .bundle(OscBundle::at(OscTime::from((3, 4))).message("/c"));
+    assert_eq!(
+        OscPacket::from(bundle),
+        OscPacket::Bundle(OscBundle {
+            timetag: (1, 2).into(),
+            content: vec![
+                OscPacket::Message(OscMessage {
+                    addr: "/a".to_string(),
+                    args: vec![OscType::Int(1)],
+                }),
+                OscPacket::Message(OscMessage {
+                    addr: "/b".to_string(),
+                    args: vec![],
+                }),
+                OscPacket::Bundle(OscBundle {
+                    timetag: (3, 4).into(),
+                    content: vec![OscPacket::Message(OscMessage {
+                        addr: "/c".to_string(),
+                        args: vec![],
+                    })],
+                }),
+            ],
+        })
+    );
+    assert!(matches!(
+        OscPacket::from(OscMessage::new("/a")),
+        OscPacket::Message(_)
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_integer_conversions() {
+    assert_eq!(OscType::from(200u8), OscType::Int(200));
+    assert_eq!(OscType::from(60_000u16), OscType::Int(60_000));
+    assert_eq!(OscType::from(-2i16), OscType::Int(-2));
+    assert_eq!(OscType::try_from(7u32), Ok(OscType::Int(7)));
+    assert!(OscType::try_from(u32::MAX).is_err());
+    assert_eq!(OscType::try_from(7usize), Ok(OscType::Int(7)));
+    assert!(OscType::try_from(usize::MAX).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_container_conversions() {
+    assert_eq!(OscType::from(&[1u8, 2][..]), OscType::Blob(vec![1, 2]));
+    assert_eq!(OscType::from(vec![1u8, 2]), OscType::Blob(vec![1, 2]));
+    assert_eq!(OscType::from(Some(1.5f32)), OscType::Float(1.5));
+    assert_eq!(OscType::from(None::<i32>), OscType::Nil);
+
+    let ints = OscArray {
+        content: vec![OscType::Int(1), OscType::Int(2)],
+    };
+    assert_eq!(OscArray::from(vec![1, 2]), ints);
+    assert_eq!(OscArray::from([1, 2]), ints);
+    assert_eq!(OscArray::from([1u8, 2]), ints);
+    assert_eq!(
+        OscArray::from(vec!["a", "b"]),
+        OscArray::from_iter(["a", "b"])
+    );
+    // Untyped integer vectors still convert to blobs
+    assert_eq!(OscType::from(vec![1, 2]), OscType::Blob(vec![1, 2]));
+    assert_eq!(
+        OscArray::from(vec![Some(true), None]),
+        OscArray {
+            content: vec![OscType::Bool(true), OscType::Nil]
+        }
+    );
+    assert_eq!(
+        OscType::from(OscTime::from((1, 2))),
+        OscType::Time((1, 2).into())
+    );
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes_conversion() {
+    assert_eq!(
+        OscType::from(bytes::Bytes::from_static(b"blob")),
+        OscType::Blob(b"blob".to_vec())
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_byte_array_conversions() {
+    let color = OscColor::from([1, 2, 3, 4]);
+    assert_eq!(
+        color,
+        OscColor {
+            red: 1,
+            green: 2,
+            blue: 3,
+            alpha: 4,
+        }
+    );
+    assert_eq!(<[u8; 4]>::from(color), [1, 2, 3, 4]);
+
+    let midi = OscMidiMessage::from([0, 0x90, 60, 127]);
+    assert_eq!(
+        midi,
+        OscMidiMessage {
+            port: 0,
+            status: 0x90,
+            data1: 60,
+            data2: 127,
+        }
+    );
+    assert_eq!(<[u8; 4]>::from(midi), [0, 0x90, 60, 127]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_packet_messages() {
+    let packet = OscPacket::Bundle(
+        OscBundle::at((1, 0))
+            .message("/a")
+            .bundle(
+                OscBundle::at((2, 0))
+                    .bundle(OscBundle::at((3, 0)).message("/b"))
+                    .message("/c"),
+            )
+            .bundle(OscBundle::at((4, 0)))
+            .message(OscMessage::new("/d").arg(1)),
+    );
+    let expected = vec![
+        (OscMessage::new("/a"), Some(OscTime::from((1, 0)))),
+        (OscMessage::new("/b"), Some(OscTime::from((3, 0)))),
+        (OscMessage::new("/c"), Some(OscTime::from((2, 0)))),
+        (OscMessage::new("/d").arg(1), Some(OscTime::from((1, 0)))),
+    ];
+    assert_eq!(
+        packet
+            .messages()
+            .map(|(msg, timetag)| (msg.clone(), timetag))
+            .collect::<Vec<_>>(),
+        expected
+    );
+    assert_eq!(packet.into_messages().collect::<Vec<_>>(), expected);
+
+    let packet = OscPacket::Message(OscMessage::new("/bare"));
+    assert_eq!(
+        packet.messages().collect::<Vec<_>>(),
+        vec![(&OscMessage::new("/bare"), None)]
+    );
+    assert_eq!(
+        packet.into_messages().collect::<Vec<_>>(),
+        vec![(OscMessage::new("/bare"), None)]
+    );
+    assert_eq!(
+        OscPacket::Bundle(OscBundle::at((1, 0))).messages().count(),
+        0
+    );
+}
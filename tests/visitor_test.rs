@@ -0,0 +1,118 @@
+#![cfg(feature = "std")]
+
+extern crate rosc;
+
+use rosc::visitor::{self, Visitor, VisitorMut};
+use rosc::{OscArray, OscBundle, OscMessage, OscPacket, OscType};
+
+#[derive(Default)]
+struct Counter {
+    packets: usize,
+    bundles: usize,
+    messages: usize,
+    args: usize,
+    arrays: usize,
+}
+
+impl Visitor for Counter {
+    fn visit_packet(&mut self, packet: &OscPacket) {
+        self.packets += 1;
+        visitor::walk_packet(self, packet);
+    }
+
+    fn visit_bundle(&mut self, bundle: &OscBundle) {
+        self.bundles += 1;
+        visitor::walk_bundle(self, bundle);
+    }
+
+    fn visit_message(&mut self, msg: &OscMessage) {
+        self.messages += 1;
+        visitor::walk_message(self, msg);
+    }
+
+    fn visit_arg(&mut self, arg: &OscType) {
+        self.args += 1;
+        visitor::walk_arg(self, arg);
+    }
+
+    fn visit_array(&mut self, array: &OscArray) {
+        self.arrays += 1;
+        visitor::walk_array(self, array);
+    }
+}
+
+fn packet() -> OscPacket {
+    OscPacket::Bundle(
+        OscBundle::at((0, 1))
+            .message(OscMessage::new("/a").arg(1).arg(OscArray::from([
+                OscType::Blob(vec![1, 2]),
+                OscArray::from([2.5f32]).into(),
+            ])))
+            .bundle(OscBundle::at((0, 2)).message(OscMessage::new("/b").arg(vec![3u8])))
+            .bundle(OscBundle::at((0, 3))),
+    )
+}
+
+#[test]
+fn test_visitor_visits_all_elements() {
+    let mut counter = Counter::default();
+    counter.visit_packet(&packet());
+    assert_eq!(counter.packets, 5);
+    assert_eq!(counter.bundles, 3);
+    assert_eq!(counter.messages, 2);
+    assert_eq!(counter.args, 6);
+    assert_eq!(counter.arrays, 2);
+}
+
+#[test]
+fn test_visitor_can_skip_children() {
+    struct Addresses(Vec<String>);
+
+    impl Visitor for Addresses {
+        fn visit_message(&mut self, msg: &OscMessage) {
+            self.0.push(msg.addr.clone());
+        }
+
+        fn visit_arg(&mut self, _arg: &OscType) {
+            panic!("arguments shouldn't be visited");
+        }
+    }
+
+    let mut addresses = Addresses(vec![]);
+    addresses.visit_packet(&packet());
+    assert_eq!(addresses.0, vec!["/a", "/b"]);
+}
+
+#[test]
+fn test_visitor_mut_transforms_in_place() {
+    struct StripBlobs;
+
+    impl VisitorMut for StripBlobs {
+        fn visit_message_mut(&mut self, msg: &mut OscMessage) {
+            msg.addr = format!("/log{}", msg.addr);
+            visitor::walk_message_mut(self, msg);
+        }
+
+        fn visit_arg_mut(&mut self, arg: &mut OscType) {
+            if let OscType::Blob(blob) = arg {
+                *arg = OscType::String(format!("<{} bytes>", blob.len()));
+            }
+            visitor::walk_arg_mut(self, arg);
+        }
+    }
+
+    let mut packet = packet();
+    StripBlobs.visit_packet_mut(&mut packet);
+    assert_eq!(
+        packet,
+        OscPacket::Bundle(
+            OscBundle::at((0, 1))
+                .message(OscMessage::new("/log/a").arg(1).arg(OscArray::from([
+                    OscType::String("<2 bytes>".to_string()),
+                    OscArray::from([2.5f32]).into(),
+                ])))
+                .bundle(OscBundle::at((0, 2)).message(OscMessage::new("/log/b").arg("<1 bytes>")))
+                .bundle(OscBundle::at((0, 3))),
+        )
+    );
+}